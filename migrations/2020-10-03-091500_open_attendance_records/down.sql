@@ -0,0 +1,4 @@
+DROP INDEX attendance_records_open_record_idx;
+
+UPDATE attendance_records SET end_time = start_time WHERE end_time IS NULL;
+ALTER TABLE attendance_records ALTER COLUMN end_time SET NOT NULL;
@@ -0,0 +1,6 @@
+ALTER TABLE attendance_records ALTER COLUMN end_time DROP NOT NULL;
+
+-- 未退勤のレコードはユーザー毎に1件まで
+CREATE UNIQUE INDEX attendance_records_open_record_idx
+    ON attendance_records (user_id)
+    WHERE end_time IS NULL;
@@ -0,0 +1,15 @@
+ALTER TABLE attendance_records ADD COLUMN break_time INTEGER NOT NULL DEFAULT 0;
+
+UPDATE attendance_records
+SET break_time = totals.break_time
+FROM (
+    SELECT record_id, (SUM(EXTRACT(EPOCH FROM (end_time - start_time))) * 1000)::INTEGER AS break_time
+    FROM breaks
+    WHERE end_time IS NOT NULL
+    GROUP BY record_id
+) AS totals
+WHERE attendance_records.id = totals.record_id;
+
+ALTER TABLE attendance_records ALTER COLUMN break_time DROP DEFAULT;
+
+DROP TABLE breaks;
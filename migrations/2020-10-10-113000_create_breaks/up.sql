@@ -0,0 +1,32 @@
+CREATE TABLE breaks (
+    id SERIAL PRIMARY KEY,
+    record_id INTEGER NOT NULL REFERENCES attendance_records (id) ON DELETE CASCADE,
+    start_time TIMESTAMP NOT NULL,
+    end_time TIMESTAMP,
+    CHECK (end_time IS NULL OR start_time <= end_time)
+);
+
+CREATE INDEX breaks_record_id_idx ON breaks (record_id);
+
+-- 休憩中(end_timeが未登録)の休憩はレコード毎に1件まで
+CREATE UNIQUE INDEX breaks_open_break_idx
+    ON breaks (record_id)
+    WHERE end_time IS NULL;
+
+-- 既存のbreak_time(ミリ秒)は、出勤時刻から始まる休憩1件として移行する.
+-- 勤務時間を超える休憩は退勤時刻で打ち切る.
+INSERT INTO breaks (record_id, start_time, end_time)
+SELECT
+    id,
+    start_time,
+    date_trunc(
+        'second',
+        LEAST(
+            start_time + break_time * INTERVAL '1 millisecond',
+            COALESCE(end_time, start_time + break_time * INTERVAL '1 millisecond')
+        )
+    )
+FROM attendance_records
+WHERE break_time > 0;
+
+ALTER TABLE attendance_records DROP COLUMN break_time;
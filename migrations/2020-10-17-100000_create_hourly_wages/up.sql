@@ -0,0 +1,7 @@
+CREATE TABLE hourly_wages (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    amount INTEGER NOT NULL CHECK (amount >= 0),
+    effective_from DATE NOT NULL,
+    UNIQUE (user_id, effective_from)
+);
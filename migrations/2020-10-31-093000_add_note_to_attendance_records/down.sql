@@ -0,0 +1 @@
+ALTER TABLE attendance_records DROP COLUMN note;
@@ -0,0 +1 @@
+ALTER TABLE attendance_records ADD COLUMN note TEXT;
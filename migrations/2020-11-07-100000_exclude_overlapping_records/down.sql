@@ -0,0 +1,3 @@
+ALTER TABLE attendance_records DROP CONSTRAINT attendance_records_no_overlap;
+
+-- btree_gistはこのmigration以前から存在した場合や他で使われている場合があるため削除しない
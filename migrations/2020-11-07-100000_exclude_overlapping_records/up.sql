@@ -0,0 +1,33 @@
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- 既存の重複レコードは自動では修正せず、一覧を出力して中断する
+DO $$
+DECLARE
+    overlap RECORD;
+    overlap_count INTEGER := 0;
+BEGIN
+    FOR overlap IN
+        SELECT a.user_id, a.id AS record_id, b.id AS other_id
+        FROM attendance_records AS a
+        JOIN attendance_records AS b
+            ON a.user_id = b.user_id
+            AND a.id < b.id
+            AND tstzrange(a.start_time, a.end_time, '[)') && tstzrange(b.start_time, b.end_time, '[)')
+        ORDER BY a.user_id, a.id, b.id
+    LOOP
+        RAISE WARNING 'user % : attendance record % overlaps with record %',
+            overlap.user_id, overlap.record_id, overlap.other_id;
+        overlap_count := overlap_count + 1;
+    END LOOP;
+
+    IF overlap_count > 0 THEN
+        RAISE EXCEPTION '% overlapping attendance record pair(s) found. Fix them before running this migration.',
+            overlap_count;
+    END IF;
+END
+$$;
+
+-- 同じユーザーの勤務時間は重複しない. 未退勤のレコードは終了時刻を無限大とみなす
+ALTER TABLE attendance_records
+    ADD CONSTRAINT attendance_records_no_overlap
+    EXCLUDE USING gist (user_id WITH =, tstzrange(start_time, end_time, '[)') WITH &&);
@@ -0,0 +1,2 @@
+-- 無効化したユーザーは勤怠記録を登録・変更できない
+ALTER TABLE users ADD COLUMN deactivated_at TIMESTAMPTZ;
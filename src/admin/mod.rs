@@ -0,0 +1,567 @@
+//! 運用者向けの管理用CLI. `at-api admin ...`で実行する.
+//!
+//! APIと同じusecaseとdriverを使用するため、検証や権限の規則はAPIと共通.
+pub mod output;
+
+use crate::config::Settings;
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use crate::domain::entity::user::User;
+use crate::driver::api_keys::ApiKeyDriver;
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::driver::integrity::IntegrityDriver;
+use crate::driver::users::UserDriver;
+use crate::usecase::api_keys::issue;
+use crate::usecase::attendance_records::import::{self, parse_time};
+use crate::usecase::attendance_records::search_by_user::{self, MAX_COUNT};
+use crate::usecase::attendance_records::summary::{self, GroupBy};
+use crate::usecase::attendance_records::update;
+use crate::usecase::integrity::check;
+use crate::usecase::users::{add as add_user, deactivate, list as user_list};
+use chrono::{DateTime, Utc};
+use diesel::pg::PgConnection;
+use output::{Format, Output, Table};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub const USAGE: &str = "usage: at-api admin [--format table|json] <command>
+
+commands:
+  users create <uid> [--time-zone <tz>]
+  users list
+  users deactivate <uid>
+  records list <uid> [--from <time>] [--to <time>] [--page <n>] [--count <n>]
+  records fix <uid> <id> [--start <time>] [--end <time>] [--note <text>]
+  records import <file> [--input-format <format>] [--mode all_or_nothing|skip_invalid]
+  api-keys issue <name> <scope>[,<scope>...] [--expires-at <time>]
+  summaries recompute <uid> --from <time> --to <time> [--group-by day|week|month]
+  check
+
+<time> is a unix time or an RFC 3339 date time (e.g. 2020-11-01T09:00:00+09:00).
+<format> is csv (default), json_lines, daily_jp, timesheet_jp, punch_en
+or a name under [import.mappings] in the settings.";
+
+#[derive(Debug)]
+pub enum AdminError {
+    /// 引数が不正.
+    Usage(String),
+    /// usecaseのエラー.
+    Data(DataAccessError),
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdminError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            AdminError::Data(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<DataAccessError> for AdminError {
+    fn from(error: DataAccessError) -> Self {
+        AdminError::Data(error)
+    }
+}
+
+fn usage(message: &str) -> AdminError {
+    AdminError::Usage(message.to_string())
+}
+
+/// 位置引数と`--name value`形式のオプション.
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Args, AdminError> {
+        let mut parsed = Args::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(option) => {
+                    let (name, value) = match option.find('=') {
+                        Some(index) => (&option[..index], option[index + 1..].to_string()),
+                        None => match iter.next() {
+                            Some(value) => (option, value.clone()),
+                            None => return Err(usage(&format!("--{} requires a value", option))),
+                        },
+                    };
+                    parsed.options.insert(name.to_string(), value);
+                }
+                None => parsed.positional.push(arg.clone()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// コマンドが受け付けないオプションをエラーとする.
+    fn allow(&self, names: &[&str]) -> Result<(), AdminError> {
+        match self
+            .options
+            .keys()
+            .find(|name| name.as_str() != "format" && !names.contains(&name.as_str()))
+        {
+            Some(name) => Err(usage(&format!("Unknown option: --{}", name))),
+            None => Ok(()),
+        }
+    }
+
+    fn option(&self, name: &str) -> Option<String> {
+        self.options.get(name).cloned()
+    }
+
+    fn parse_option<T: FromStr>(&self, name: &str) -> Result<Option<T>, AdminError> {
+        self.options
+            .get(name)
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|_| usage(&format!("Invalid value for --{}: {}", name, value)))
+            })
+            .transpose()
+    }
+
+    fn time(&self, name: &str) -> Result<Option<i64>, AdminError> {
+        self.options
+            .get(name)
+            .map(|value| {
+                parse_time(value)
+                    .ok_or_else(|| usage(&format!("Invalid time for --{}: {}", name, value)))
+            })
+            .transpose()
+    }
+
+    fn required_time(&self, name: &str) -> Result<i64, AdminError> {
+        self.time(name)?
+            .ok_or_else(|| usage(&format!("--{} is required", name)))
+    }
+}
+
+fn format_time(datetime: Option<DateTime<Utc>>) -> String {
+    datetime
+        .map(|datetime| datetime.to_rfc3339())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 引数に従ってコマンドを実行し、出力と成否を返す.
+pub fn run(
+    connection: &PgConnection,
+    settings: &Settings,
+    args: &[String],
+) -> Result<(String, bool), AdminError> {
+    let args = Args::parse(args)?;
+    let format = args
+        .option("format")
+        .map(|format| format.parse::<Format>().map_err(|e| usage(&e)))
+        .transpose()?
+        .unwrap_or(Format::Table);
+
+    let positional = args
+        .positional
+        .iter()
+        .map(|arg| arg.as_str())
+        .collect::<Vec<&str>>();
+    let output = match positional.as_slice() {
+        ["users", "create", uid] => create_user(connection, settings, &args, uid)?,
+        ["users", "list"] => list_users(connection, &args)?,
+        ["users", "deactivate", uid] => deactivate_user(connection, &args, uid)?,
+        ["records", "list", uid] => list_records(connection, &args, uid)?,
+        ["records", "fix", uid, id] => fix_record(connection, &args, uid, id)?,
+        ["records", "import", path] => import_records(connection, settings, &args, path)?,
+        ["api-keys", "issue", name, scopes] => issue_api_key(connection, &args, name, scopes)?,
+        ["summaries", "recompute", uid] => recompute_summary(connection, &args, uid)?,
+        ["check"] => check_integrity(connection, &args)?,
+        [] => return Err(usage("A command is required")),
+        _ => return Err(usage(&format!("Unknown command: {}", positional.join(" ")))),
+    };
+
+    Ok((output.render(format), output.success))
+}
+
+fn user_table(users: &[User]) -> Table {
+    let mut table = Table::new(&["id", "uid", "time_zone", "deactivated_at"]);
+    for user in users {
+        table.push(vec![
+            user.id.to_string(),
+            user.uid.clone(),
+            user.time_zone.clone(),
+            format_time(user.deactivated_at),
+        ]);
+    }
+    table
+}
+
+fn create_user(
+    connection: &PgConnection,
+    settings: &Settings,
+    args: &Args,
+    uid: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&["time-zone"])?;
+    // APIと同じく、省略した場合は設定の初期値を使う
+    let input = add_user::InputData {
+        uid: uid.to_string(),
+        time_zone: Some(
+            args.option("time-zone")
+                .unwrap_or_else(|| settings.users.default_time_zone.clone()),
+        ),
+    };
+    let user = add_user::execute(UserDriver::new(connection), input)?;
+    Ok(Output::new(user_table(std::slice::from_ref(&user)), &user))
+}
+
+fn list_users(connection: &PgConnection, args: &Args) -> Result<Output, AdminError> {
+    args.allow(&[])?;
+    let users = user_list::execute(UserDriver::new(connection))?;
+    Ok(Output::new(user_table(&users), &users))
+}
+
+fn deactivate_user(
+    connection: &PgConnection,
+    args: &Args,
+    uid: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&[])?;
+    let input = deactivate::InputData {
+        uid: uid.to_string(),
+    };
+    let user = deactivate::execute(UserDriver::new(connection), input)?;
+    Ok(Output::new(user_table(std::slice::from_ref(&user)), &user))
+}
+
+fn record_table(records: &[AttendanceRecord]) -> Table {
+    let mut table = Table::new(&[
+        "id",
+        "start_time",
+        "end_time",
+        "break_minutes",
+        "breaks",
+        "note",
+    ]);
+    for record in records {
+        table.push(vec![
+            record.id.to_string(),
+            format_time(Some(record.start_time)),
+            format_time(record.end_time),
+            (record.break_seconds / 60).to_string(),
+            record.breaks.len().to_string(),
+            record.note.clone().unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    table
+}
+
+fn list_records(connection: &PgConnection, args: &Args, uid: &str) -> Result<Output, AdminError> {
+    args.allow(&["from", "to", "page", "count"])?;
+    let input = search_by_user::InputData {
+        uid: uid.to_string(),
+        page: args.parse_option("page")?.unwrap_or(1),
+        count: args.parse_option("count")?.unwrap_or(MAX_COUNT),
+        from: args.time("from")?,
+        to: args.time("to")?,
+        with_total: true,
+        ..search_by_user::InputData::default()
+    };
+    let page = search_by_user::execute(AttendanceRecordDriver::new(connection), input)?;
+    Ok(Output::new(record_table(&page.records), &page))
+}
+
+fn fix_record(
+    connection: &PgConnection,
+    args: &Args,
+    uid: &str,
+    id: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&["start", "end", "note"])?;
+    let id = id
+        .parse::<i32>()
+        .map_err(|_| usage(&format!("Invalid record id: {}", id)))?;
+    let input = update::InputData {
+        uid: uid.to_string(),
+        id,
+        start_time: args.time("start")?,
+        end_time: args.time("end")?,
+        breaks: None,
+        note: args.option("note"),
+    };
+    if input.start_time.is_none() && input.end_time.is_none() && input.note.is_none() {
+        return Err(usage("One of --start, --end or --note is required"));
+    }
+    update::execute(AttendanceRecordDriver::new(connection), input)?;
+
+    let mut table = Table::new(&["id", "result"]);
+    table.push(vec![id.to_string(), "updated".to_string()]);
+    Ok(Output::new(
+        table,
+        &serde_json::json!({ "id": id, "updated": true }),
+    ))
+}
+
+fn import_records(
+    connection: &PgConnection,
+    settings: &Settings,
+    args: &Args,
+    path: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&["input-format", "mode"])?;
+    let format = args
+        .option("input-format")
+        .unwrap_or_else(|| "csv".to_string());
+    let importer = import::importer(&format, &settings.import.mappings)
+        .map_err(|_| usage(&format!("Invalid value for --input-format: {}", format)))?;
+    let mode = match args.option("mode").as_deref() {
+        None | Some("all_or_nothing") => import::Mode::AllOrNothing,
+        Some("skip_invalid") => import::Mode::SkipInvalid,
+        Some(other) => return Err(usage(&format!("Invalid value for --mode: {}", other))),
+    };
+    let body = std::fs::read_to_string(path)
+        .map_err(|e| usage(&format!("Failed to read {}: {}", path, e)))?;
+    let input = import::InputData { mode, body };
+    let report = import::execute(
+        AttendanceRecordDriver::new(connection),
+        importer.as_ref(),
+        input,
+    )?;
+
+    let mut table = Table::new(&["line", "uid", "record_id", "errors"]);
+    for row in &report.rows {
+        table.push(vec![
+            row.line.to_string(),
+            row.uid.clone().unwrap_or_else(|| "-".to_string()),
+            row.record_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            row.errors
+                .iter()
+                .map(|error| format!("{} {}", error.field, error.message))
+                .collect::<Vec<String>>()
+                .join("; "),
+        ]);
+    }
+    Ok(Output {
+        success: report.committed && report.failed == 0,
+        ..Output::new(table, &report)
+    })
+}
+
+fn issue_api_key(
+    connection: &PgConnection,
+    args: &Args,
+    name: &str,
+    scopes: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&["expires-at"])?;
+    let input = issue::InputData {
+        name: name.to_string(),
+        scopes: scopes.split(',').map(|scope| scope.to_string()).collect(),
+        expires_at: args.time("expires-at")?,
+    };
+    let issued = issue::execute(ApiKeyDriver::new(connection), input)?;
+
+    let mut table = Table::new(&["id", "name", "scopes", "expires_at", "key"]);
+    table.push(vec![
+        issued.api_key.id.to_string(),
+        issued.api_key.name.clone(),
+        issued.api_key.scopes.join(","),
+        format_time(issued.api_key.expires_at),
+        issued.key.clone(),
+    ]);
+    Ok(Output::new(table, &issued))
+}
+
+fn recompute_summary(
+    connection: &PgConnection,
+    args: &Args,
+    uid: &str,
+) -> Result<Output, AdminError> {
+    args.allow(&["from", "to", "group-by"])?;
+    let group_by = match args.option("group-by").as_deref() {
+        None | Some("day") => GroupBy::Day,
+        Some("week") => GroupBy::Week,
+        Some("month") => GroupBy::Month,
+        Some(other) => return Err(usage(&format!("Invalid value for --group-by: {}", other))),
+    };
+    let input = summary::InputData {
+        uid: uid.to_string(),
+        from: args.required_time("from")?,
+        to: args.required_time("to")?,
+        group_by,
+    };
+    let summary = summary::execute(AttendanceRecordDriver::new(connection), input)?;
+
+    let mut table = Table::new(&[
+        "start",
+        "records",
+        "worked_minutes",
+        "break_minutes",
+        "overtime_minutes",
+        "late_night_minutes",
+        "holiday_minutes",
+    ]);
+    let rows = summary
+        .items
+        .iter()
+        .map(|item| {
+            (
+                format_time(Some(item.start)),
+                item.record_count,
+                item.break_minutes,
+                item.working_time,
+            )
+        })
+        .chain(std::iter::once((
+            "total".to_string(),
+            summary.total.record_count,
+            summary.total.break_minutes,
+            summary.total.working_time,
+        )));
+    for (start, record_count, break_minutes, working_time) in rows {
+        table.push(vec![
+            start,
+            record_count.to_string(),
+            working_time.worked_minutes.to_string(),
+            break_minutes.to_string(),
+            working_time.overtime_minutes.to_string(),
+            working_time.late_night_minutes.to_string(),
+            working_time.holiday_minutes.to_string(),
+        ]);
+    }
+    Ok(Output::new(table, &summary))
+}
+
+fn check_integrity(connection: &PgConnection, args: &Args) -> Result<Output, AdminError> {
+    args.allow(&[])?;
+    let issues = check::execute(IntegrityDriver::new(connection))?;
+
+    let mut table = Table::new(&["check", "uid", "record_id", "detail"]);
+    for issue in &issues {
+        table.push(vec![
+            issue.check.clone(),
+            issue.uid.clone(),
+            issue
+                .record_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            issue.detail.clone(),
+        ]);
+    }
+    Ok(Output {
+        success: issues.is_empty(),
+        ..Output::new(table, &issues)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config;
+    use crate::database_utils::pool::test_util;
+    use serde_json::Value;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    fn run(connection: &PgConnection, args: &[String]) -> Result<(String, bool), AdminError> {
+        super::run(connection, &config::test_util::settings(), args)
+    }
+
+    fn json(connection: &PgConnection, line: &str) -> Value {
+        let (text, _) = run(connection, &args(&format!("--format json {}", line))).unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn parse_test() {
+        let parsed =
+            Args::parse(&args("users create alice --time-zone=UTC --format json")).unwrap();
+        assert_eq!(parsed.positional, vec!["users", "create", "alice"]);
+        assert_eq!(parsed.option("time-zone"), Some("UTC".to_string()));
+        assert_eq!(parsed.option("format"), Some("json".to_string()));
+        assert!(parsed.allow(&["time-zone"]).is_ok());
+        assert!(parsed.allow(&[]).is_err());
+
+        assert!(Args::parse(&args("users create alice --time-zone")).is_err());
+
+        assert_eq!(parse_time("1604188800"), Some(1_604_188_800));
+        assert_eq!(parse_time("2020-11-01T09:00:00+09:00"), Some(1_604_188_800));
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[test]
+    fn admin_scenario() {
+        let connection = test_util::connection_init();
+
+        let user = json(&connection, "users create admin-test --time-zone UTC");
+        assert_eq!(user["uid"], "admin-test");
+        assert_eq!(user["time_zone"], "UTC");
+
+        let (text, success) = run(&connection, &args("users list")).unwrap();
+        assert!(success);
+        assert!(text.starts_with("id"));
+        assert!(text.contains("admin-test"));
+
+        let page = json(&connection, "records list admin-test");
+        assert_eq!(page["total"], 0);
+
+        let summary = json(
+            &connection,
+            "summaries recompute admin-test --from 0 --to 1604188800 --group-by month",
+        );
+        assert_eq!(summary["total"]["record_count"], 0);
+
+        let path = std::env::temp_dir().join("at-api-admin-import.jsonl");
+        std::fs::write(
+            &path,
+            "{\"uid\": \"admin-test\", \"start_time\": 1604188800, \"end_time\": 1604217600}\n",
+        )
+        .unwrap();
+        let report = json(
+            &connection,
+            &format!(
+                "records import {} --input-format json_lines",
+                path.display()
+            ),
+        );
+        assert_eq!(report["imported"], 1);
+        std::fs::remove_file(&path).unwrap();
+
+        let user = json(&connection, "users deactivate admin-test");
+        assert!(user["deactivated_at"].is_string());
+
+        let issued = json(&connection, "api-keys issue operator keys:admin");
+        assert!(issued["key"].as_str().unwrap().starts_with("atk_"));
+
+        let result = run(&connection, &args("check --format json"));
+        assert!(result.is_ok());
+
+        assert!(matches!(
+            run(&connection, &args("users remove admin-test")),
+            Err(AdminError::Usage(_))
+        ));
+        assert!(matches!(
+            run(&connection, &args("records fix admin-test 1")),
+            Err(AdminError::Usage(_))
+        ));
+        assert!(matches!(
+            run(&connection, &args("users create admin-test")),
+            Err(AdminError::Data(DataAccessError::Conflict(_)))
+        ));
+
+        // time zoneを省略した場合は設定の初期値
+        let mut settings = config::test_util::settings();
+        settings.users.default_time_zone = "Europe/London".to_string();
+        let (text, _) = super::run(
+            &connection,
+            &settings,
+            &args("--format json users create admin-test-default"),
+        )
+        .unwrap();
+        let user: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(user["time_zone"], "Europe/London");
+    }
+}
@@ -0,0 +1,126 @@
+//! 管理用CLIの出力形式.
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// 人が読むための表
+    Table,
+    /// スクリプトから読むためのJSON
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown format: {}! Use table or json.", s)),
+        }
+    }
+}
+
+/// 列幅を揃えた表.
+#[derive(Debug, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Table {
+        Table {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn render(&self) -> String {
+        let mut widths = self
+            .headers
+            .iter()
+            .map(|header| header.chars().count())
+            .collect::<Vec<usize>>();
+        for row in &self.rows {
+            for (index, cell) in row.iter().enumerate() {
+                if let Some(width) = widths.get_mut(index) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+        }
+
+        let line = |cells: &[String]| {
+            cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let padding = width - cell.chars().count();
+                    format!("{}{}", cell, " ".repeat(padding))
+                })
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        let mut lines = vec![line(&self.headers)];
+        lines.extend(self.rows.iter().map(|row| line(row)));
+        lines.join("\n")
+    }
+}
+
+/// コマンドの結果. 表とJSONの両方の表現を持つ.
+#[derive(Debug)]
+pub struct Output {
+    pub table: Table,
+    pub json: Value,
+    /// 異常を検出した場合はfalse. 終了コードに反映する
+    pub success: bool,
+}
+
+impl Output {
+    pub fn new<T: Serialize>(table: Table, value: &T) -> Output {
+        Output {
+            table,
+            json: serde_json::to_value(value).unwrap_or(Value::Null),
+            success: true,
+        }
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table.render(),
+            Format::Json => serde_json::to_string_pretty(&self.json).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render_test() {
+        let mut table = Table::new(&["id", "uid", "note"]);
+        table.push(vec![
+            "1".to_string(),
+            "alice".to_string(),
+            "早退".to_string(),
+        ]);
+        table.push(vec!["10".to_string(), "bob".to_string(), "-".to_string()]);
+        let output = Output::new(table, &serde_json::json!([{"id": 1}]));
+
+        assert_eq!(
+            output.render(Format::Table),
+            "id  uid    note\n1   alice  早退\n10  bob    -"
+        );
+        assert_eq!(output.render(Format::Json), "[\n  {\n    \"id\": 1\n  }\n]");
+        assert!("csv".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! api keyによる認証と、scopeによる認可.
+pub mod jwt;
+
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::domain::entity::api_key::{ApiKey, Scope};
+use crate::driver::api_keys::ApiKeyDriver;
+use crate::usecase::api_keys::authenticate;
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::{Error, HttpMessage};
+use chrono::Utc;
+use futures::future::{ok, Ready};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// 認証済みのapi key.
+pub fn api_key(req: &ServiceRequest) -> Option<ApiKey> {
+    req.extensions().get::<ApiKey>().cloned()
+}
+
+/// `X-Api-Key`ヘッダーの鍵を検証し、認証済みのapi keyをrequestのextensionsに格納するmiddleware.
+pub struct Authentication;
+
+impl<S, B> Transform<S> for Authentication
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthenticationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuthenticationMiddleware {
+            service: Rc::new(RefCell::new(service)),
+        })
+    }
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: Rc<RefCell<S>>,
+}
+
+impl<S, B> Service for AuthenticationMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let key = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let pool = req.app_data::<DbPool>();
+
+        Box::pin(async move {
+            let key = key.ok_or_else(|| {
+                DataAccessError::Unauthorized("x-api-key header is required!".to_string())
+            })?;
+            let pool = pool.ok_or_else(|| DataAccessError::Internal(None))?;
+            let api_key = Repository::new(&pool)
+                .run(move |connection| {
+                    authenticate::execute(ApiKeyDriver::new(connection), &key, Utc::now())
+                })
+                .await?;
+
+            req.extensions_mut().insert(api_key);
+            let fut = service.borrow_mut().call(req);
+            fut.await
+        })
+    }
+}
+
+/// 認証済みのapi keyにscopeが許可されているか確認するmiddleware.
+///
+/// GET, HEADは`read`、それ以外のmethodは`write`のscopeを必要とする.
+#[derive(Clone, Copy)]
+pub struct RequireScope {
+    read: Scope,
+    write: Scope,
+}
+
+impl RequireScope {
+    pub fn new(read: Scope, write: Scope) -> RequireScope {
+        RequireScope { read, write }
+    }
+
+    /// methodによらず同じscopeを必要とする.
+    pub fn all(scope: Scope) -> RequireScope {
+        RequireScope::new(scope, scope)
+    }
+
+    fn required(&self, method: &Method) -> Scope {
+        if method == Method::GET || method == Method::HEAD {
+            self.read
+        } else {
+            self.write
+        }
+    }
+}
+
+impl<S, B> Transform<S> for RequireScope
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireScopeMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireScopeMiddleware {
+            service,
+            require: *self,
+        })
+    }
+}
+
+pub struct RequireScopeMiddleware<S> {
+    service: S,
+    require: RequireScope,
+}
+
+impl<S, B> Service for RequireScopeMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let scope = self.require.required(req.method());
+        let error = match api_key(&req) {
+            Some(api_key) if api_key.has_scope(scope) => None,
+            Some(_) => Some(DataAccessError::Forbidden(format!(
+                "The api key does not have the {} scope!",
+                scope
+            ))),
+            None => Some(DataAccessError::Unauthorized(
+                "x-api-key header is required!".to_string(),
+            )),
+        };
+
+        match error {
+            Some(error) => Box::pin(async move { Err(error.into()) }),
+            None => Box::pin(self.service.call(req)),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_util {
+    use super::*;
+
+    /// すべてのscopeを持つapi key.
+    pub fn all_scopes_api_key() -> ApiKey {
+        ApiKey {
+            id: 0,
+            name: "test".to_string(),
+            prefix: "atk_test".to_string(),
+            scopes: Scope::ALL.iter().map(|scope| scope.to_string()).collect(),
+            expires_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// routeのテストで認証を省略する. `App::wrap_fn`に渡す.
+    pub fn authorize_all<S>(req: ServiceRequest, service: &mut S) -> S::Future
+    where
+        S: Service<Request = ServiceRequest>,
+    {
+        req.extensions_mut().insert(all_scopes_api_key());
+        service.call(req)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util::setup_connection_pool;
+    use crate::usecase::api_keys::issue::IssueApiKeyUseCase;
+    use actix_web::{test, web, App, HttpResponse};
+
+    #[actix_rt::test]
+    async fn authorization_test() {
+        let pool = setup_connection_pool();
+        let issue = |scopes: Vec<Scope>| {
+            let connection = pool.get().unwrap();
+            ApiKeyDriver::new(&connection)
+                .issue("test".to_string(), scopes, None)
+                .unwrap()
+                .key
+        };
+        let reader = issue(vec![Scope::RecordsRead]);
+        let writer = issue(vec![Scope::RecordsRead, Scope::RecordsWrite]);
+
+        let mut app = test::init_service(
+            App::new().data(pool.clone()).service(
+                web::scope("")
+                    .wrap(RequireScope::new(Scope::RecordsRead, Scope::RecordsWrite))
+                    .wrap(Authentication)
+                    .route("/records", web::get().to(HttpResponse::Ok))
+                    .route("/records", web::post().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+
+        let cases = vec![
+            (Method::GET, None, 401),
+            (Method::GET, Some("atk_unknown"), 401),
+            (Method::GET, Some(reader.as_str()), 200),
+            (Method::POST, Some(reader.as_str()), 403),
+            (Method::POST, Some(writer.as_str()), 200),
+        ];
+        for (method, key, status) in cases {
+            let mut req = test::TestRequest::default().method(method).uri("/records");
+            if let Some(key) = key {
+                req = req.header(API_KEY_HEADER, key);
+            }
+            let resp = app.call(req.to_request()).await;
+            let actual = match resp {
+                Ok(resp) => resp.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            assert_eq!(actual.as_u16(), status, "{:?}", key);
+        }
+    }
+}
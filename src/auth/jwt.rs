@@ -0,0 +1,377 @@
+//! エンドユーザーのID token(JWT)の検証.
+//!
+//! 公開鍵はJWKS(ファイルまたはURL)から読み込み、RS256とES256の署名のみ受け付ける.
+use crate::database_utils::error::DataAccessError;
+use crate::logging;
+use actix_web::dev::Payload;
+use actix_web::error::BlockingError;
+use actix_web::http::header;
+use actix_web::{web, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use std::fs;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// 未知のkidを受け取った場合に、JWKSを再取得する最短の間隔.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// JWKSの取得元.
+#[derive(Debug, Clone)]
+pub enum JwksSource {
+    File(String),
+    Url(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub source: JwksSource,
+    /// `iss` claimとして受け付ける値
+    pub issuer: String,
+    /// `aud` claimとして受け付ける値
+    pub audience: String,
+    /// 有効期限の判定で許容する時刻のずれ(秒)
+    pub leeway_seconds: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+struct Keys {
+    set: JwkSet,
+    fetched_at: Instant,
+}
+
+/// ID tokenを検証する. `App::data`で共有する.
+pub struct Verifier {
+    config: JwtConfig,
+    keys: RwLock<Keys>,
+}
+
+impl Verifier {
+    pub fn new(config: JwtConfig) -> Result<Verifier, String> {
+        let set = load(&config.source)?;
+        Ok(Verifier {
+            config,
+            keys: RwLock::new(Keys {
+                set,
+                fetched_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// 署名と有効期限などを検証し、`sub` claim(uid)を返す.
+    ///
+    /// JWKSをURLから再取得する場合があるため、ブロッキング処理用のthreadで呼び出す.
+    pub fn verify(&self, token: &str) -> Result<String, DataAccessError> {
+        let header = decode_header(token).map_err(|_| invalid_token())?;
+        let jwk = self.find_key(header.kid.as_deref())?;
+
+        let algorithm = match &jwk.algorithm {
+            AlgorithmParameters::RSA(_) => Algorithm::RS256,
+            AlgorithmParameters::EllipticCurve(params) if params.curve == EllipticCurve::P256 => {
+                Algorithm::ES256
+            }
+            // HMACの共通鍵などは受け付けない
+            _ => return Err(invalid_token()),
+        };
+        if header.alg != algorithm {
+            return Err(invalid_token());
+        }
+        let key = DecodingKey::from_jwk(&jwk).map_err(|_| invalid_token())?;
+
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = self.config.leeway_seconds;
+        validation.validate_nbf = true;
+        validation.set_required_spec_claims(&["exp", "sub", "iss", "aud"]);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.audience]);
+
+        let data = decode::<Claims>(token, &key, &validation).map_err(|_| invalid_token())?;
+        if data.claims.sub.is_empty() {
+            return Err(invalid_token());
+        }
+        Ok(data.claims.sub)
+    }
+
+    /// kidに一致する鍵. 見つからない場合は鍵のローテーションに備えて再取得する.
+    fn find_key(&self, kid: Option<&str>) -> Result<Jwk, DataAccessError> {
+        if let Some(jwk) = self.cached_key(kid) {
+            return Ok(jwk);
+        }
+
+        let refreshable = self.keys.read().unwrap().fetched_at.elapsed() >= REFRESH_INTERVAL;
+        if !refreshable {
+            return Err(invalid_token());
+        }
+        match load(&self.config.source) {
+            Ok(set) => {
+                *self.keys.write().unwrap() = Keys {
+                    set,
+                    fetched_at: Instant::now(),
+                };
+            }
+            Err(error) => {
+                log::error!("failed to refresh JWKS: {}", error);
+                return Err(DataAccessError::Unavailable(
+                    "Failed to load the signing keys!".to_string(),
+                ));
+            }
+        }
+
+        self.cached_key(kid).ok_or_else(invalid_token)
+    }
+
+    fn cached_key(&self, kid: Option<&str>) -> Option<Jwk> {
+        let keys = self.keys.read().unwrap();
+        match kid {
+            Some(kid) => keys.set.find(kid).cloned(),
+            // kidが無い場合は鍵が1つの場合のみ
+            None if keys.set.keys.len() == 1 => keys.set.keys.first().cloned(),
+            None => None,
+        }
+    }
+}
+
+fn invalid_token() -> DataAccessError {
+    DataAccessError::Unauthorized("Invalid id token!".to_string())
+}
+
+fn load(source: &JwksSource) -> Result<JwkSet, String> {
+    match source {
+        JwksSource::File(path) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            serde_json::from_str(&text).map_err(|e| format!("{}: {}", path, e))
+        }
+        JwksSource::Url(url) => ureq::get(url)
+            .timeout(Duration::from_secs(5))
+            .call()
+            .map_err(|e| format!("{}: {}", url, e))?
+            .into_string()
+            .map_err(|e| format!("{}: {}", url, e))
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| format!("{}: {}", url, e))),
+    }
+}
+
+/// `Authorization: Bearer <ID token>`で認証したユーザー.
+///
+/// # example
+///
+/// ```ignore
+/// pub async fn route(pool: web::Data<DbPool>, user: AuthenticatedUser) -> ... {
+///     let input = InputData { uid: user.uid };
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub uid: String,
+}
+
+impl AuthenticatedUser {
+    /// `Authorization`ヘッダーのID tokenを検証する.
+    ///
+    /// 検証済みのユーザーはrequestのextensionsに格納し、同じrequestでは再検証しない.
+    pub fn authenticate(
+        req: &HttpRequest,
+    ) -> LocalBoxFuture<'static, Result<AuthenticatedUser, DataAccessError>> {
+        if let Some(user) = req.extensions().get::<AuthenticatedUser>() {
+            let user = user.clone();
+            return Box::pin(async move { Ok(user) });
+        }
+
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string());
+        let verifier = req.app_data::<web::Data<Verifier>>().cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            let token = token.ok_or_else(|| {
+                DataAccessError::Unauthorized("Authorization header is required!".to_string())
+            })?;
+            let verifier = verifier.ok_or(DataAccessError::Internal(None))?;
+            let uid = web::block(move || verifier.verify(&token))
+                .await
+                .map_err(|e| match e {
+                    BlockingError::Error(e) => e,
+                    BlockingError::Canceled => {
+                        DataAccessError::Unavailable("Blocking operation was canceled!".to_string())
+                    }
+                })?;
+
+            logging::set_uid(&uid);
+            let user = AuthenticatedUser { uid };
+            req.extensions_mut().insert(user.clone());
+            Ok(user)
+        })
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = DataAccessError;
+    type Future = LocalBoxFuture<'static, Result<AuthenticatedUser, DataAccessError>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        AuthenticatedUser::authenticate(req)
+    }
+}
+
+#[cfg(test)]
+pub mod test_util {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde::Serialize;
+
+    const EC_PRIVATE_KEY: &[u8] = include_bytes!("../../testdata/jwt/ec_private.pem");
+    const RSA_PRIVATE_KEY: &[u8] = include_bytes!("../../testdata/jwt/rsa_private.pem");
+    pub const ISSUER: &str = "https://securetoken.example.com/at-api";
+    pub const AUDIENCE: &str = "at-api";
+
+    #[derive(Serialize)]
+    pub struct TestClaims {
+        pub sub: String,
+        pub iss: String,
+        pub aud: String,
+        pub exp: i64,
+    }
+
+    impl TestClaims {
+        pub fn new(uid: &str) -> TestClaims {
+            TestClaims {
+                sub: uid.to_string(),
+                iss: ISSUER.to_string(),
+                aud: AUDIENCE.to_string(),
+                exp: chrono::Utc::now().timestamp() + 3600,
+            }
+        }
+    }
+
+    pub fn config() -> JwtConfig {
+        JwtConfig {
+            source: JwksSource::File(format!(
+                "{}/testdata/jwt/jwks.json",
+                env!("CARGO_MANIFEST_DIR")
+            )),
+            issuer: ISSUER.to_string(),
+            audience: AUDIENCE.to_string(),
+            leeway_seconds: 0,
+        }
+    }
+
+    pub fn verifier() -> web::Data<Verifier> {
+        web::Data::new(Verifier::new(config()).unwrap())
+    }
+
+    pub fn sign(claims: &TestClaims, algorithm: Algorithm) -> String {
+        let (kid, key) = match algorithm {
+            Algorithm::RS256 => ("test-rsa", EncodingKey::from_rsa_pem(RSA_PRIVATE_KEY)),
+            _ => ("test-ec", EncodingKey::from_ec_pem(EC_PRIVATE_KEY)),
+        };
+        let mut header = Header::new(algorithm);
+        header.kid = Some(kid.to_string());
+        encode(&header, claims, &key.unwrap()).unwrap()
+    }
+
+    /// `Authorization`ヘッダーの値.
+    pub fn bearer(uid: &str) -> String {
+        format!("Bearer {}", sign(&TestClaims::new(uid), Algorithm::ES256))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde::Serialize;
+
+    #[test]
+    fn verify_test() {
+        let verifier = Verifier::new(config()).unwrap();
+
+        for algorithm in &[Algorithm::ES256, Algorithm::RS256] {
+            let token = sign(&TestClaims::new("asdfghjkl"), *algorithm);
+            assert_eq!(verifier.verify(&token).unwrap(), "asdfghjkl");
+        }
+
+        let expired = TestClaims {
+            exp: chrono::Utc::now().timestamp() - 60,
+            ..TestClaims::new("asdfghjkl")
+        };
+        let other_issuer = TestClaims {
+            iss: "https://evil.example.com".to_string(),
+            ..TestClaims::new("asdfghjkl")
+        };
+        let other_audience = TestClaims {
+            aud: "other-api".to_string(),
+            ..TestClaims::new("asdfghjkl")
+        };
+        for claims in &[expired, other_issuer, other_audience] {
+            let token = sign(claims, Algorithm::ES256);
+            assert!(matches!(
+                verifier.verify(&token),
+                Err(DataAccessError::Unauthorized(_))
+            ));
+        }
+
+        // `iss`・`aud`を含まない
+        #[derive(Serialize)]
+        struct SubjectOnly {
+            sub: String,
+            exp: i64,
+        }
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("test-ec".to_string());
+        let token = encode(
+            &header,
+            &SubjectOnly {
+                sub: "asdfghjkl".to_string(),
+                exp: chrono::Utc::now().timestamp() + 3600,
+            },
+            &EncodingKey::from_ec_pem(include_bytes!("../../testdata/jwt/ec_private.pem")).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verifier.verify(&token),
+            Err(DataAccessError::Unauthorized(_))
+        ));
+
+        // 公開鍵を共通鍵として使用した署名は受け付けない
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("test-ec".to_string());
+        let token = encode(
+            &header,
+            &TestClaims::new("asdfghjkl"),
+            &EncodingKey::from_secret(b"N0X5XX7-2Wq_kttE17cEwNqGgogWAyHNY9W3HIH5zGQ"),
+        )
+        .unwrap();
+        assert!(verifier.verify(&token).is_err());
+
+        // 署名が一致しない
+        let mut token = sign(&TestClaims::new("asdfghjkl"), Algorithm::ES256);
+        token.push('x');
+        assert!(verifier.verify(&token).is_err());
+
+        // 未知の鍵. 直前に取得したJWKSは再取得しない
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("unknown".to_string());
+        let token = encode(
+            &header,
+            &TestClaims::new("asdfghjkl"),
+            &EncodingKey::from_ec_pem(include_bytes!("../../testdata/jwt/ec_private.pem")).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verifier.verify(&token),
+            Err(DataAccessError::Unauthorized(_))
+        ));
+        assert!(verifier.verify("not a token").is_err());
+    }
+}
@@ -0,0 +1,715 @@
+//! アプリケーションの設定.
+//!
+//! TOMLファイルを読み込み、環境変数で上書きしたうえで起動時に検証する.
+//! ファイルは`AT_API_CONFIG`で指定し、未指定の場合は`config.toml`が存在すれば読み込む.
+use crate::auth::jwt::{JwksSource, JwtConfig};
+use crate::database_utils::pool::PoolConfig;
+use crate::domain::entity::payroll::PremiumRates;
+use crate::domain::entity::user::validate_time_zone;
+use crate::rate_limit::RateLimitConfig;
+use crate::usecase::attendance_records::import::{ColumnMapping, BUILTIN_FORMATS};
+use dotenv::dotenv;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const CONFIG_PATH_ENV: &str = "AT_API_CONFIG";
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// 検証済みの設定.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub database: DatabaseSettings,
+    pub rate_limit: RateLimitConfig,
+    pub jwt: JwtConfig,
+    pub users: UserSettings,
+    pub import: ImportSettings,
+    pub payroll: PayrollSettings,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerSettings {
+    pub port: u16,
+    /// JSONのbodyの最大サイズ(byte)
+    pub json_limit: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseSettings {
+    pub url: String,
+    pub pool: PoolConfig,
+    /// 起動時に未適用のmigrationを実行するか
+    pub run_migrations: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserSettings {
+    /// 登録時にtime zoneを省略した場合のIANA time zone名
+    pub default_time_zone: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportSettings {
+    /// 一括登録で`format`に指定できる列の対応. キーが形式の名前
+    pub mappings: HashMap<String, ColumnMapping>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PayrollSettings {
+    /// 支給額の計算に使う割増率. 省略した項目は法定の割増率
+    pub rates: PremiumRates,
+}
+
+/// 設定を読み込めない、または不正.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// ファイルを読み込めない.
+    Read { path: String, message: String },
+    /// TOMLとして不正、または未知の項目がある.
+    Parse { path: String, message: String },
+    /// 値が不正. 項目毎のエラーを持つ.
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, message } => {
+                write!(f, "failed to read {}: {}", path, message)
+            }
+            ConfigError::Parse { path, message } => {
+                write!(f, "failed to parse {}: {}", path, message)
+            }
+            ConfigError::Invalid(errors) => write!(f, "invalid settings: {}", errors.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileSettings {
+    server: FileServer,
+    database: FileDatabase,
+    rate_limit: FileRateLimit,
+    jwt: FileJwt,
+    users: FileUsers,
+    import: FileImport,
+    payroll: FilePayroll,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileServer {
+    port: Option<u16>,
+    json_limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileDatabase {
+    url: Option<String>,
+    pool_max_size: Option<u32>,
+    pool_min_idle: Option<u32>,
+    pool_timeout_secs: Option<u64>,
+    run_migrations: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileRateLimit {
+    interval_secs: Option<u64>,
+    max_requests: Option<u32>,
+    api_keys: HashMap<String, u32>,
+    uids: HashMap<String, u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileJwt {
+    jwks_url: Option<String>,
+    jwks_file: Option<String>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    leeway_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileUsers {
+    default_time_zone: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileImport {
+    mappings: HashMap<String, ColumnMapping>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilePayroll {
+    overtime_rate: Option<i64>,
+    overtime_over_60h_rate: Option<i64>,
+    holiday_rate: Option<i64>,
+    late_night_rate: Option<i64>,
+}
+
+impl Settings {
+    /// 設定ファイルと環境変数から読み込む.
+    pub fn load() -> Result<Settings, ConfigError> {
+        let (path, text) = read_file()?;
+        Settings::from_sources(&path, text.as_deref(), |key| env::var(key).ok())
+    }
+
+    /// TOMLの内容と環境変数の取得関数から読み込む.
+    pub fn from_sources<F>(path: &str, text: Option<&str>, env: F) -> Result<Settings, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut file = parse_file(path, text)?;
+
+        let mut errors = Vec::new();
+        Overrides {
+            env: &env,
+            errors: &mut errors,
+        }
+        .apply(&mut file);
+        let settings = file.validate(&mut errors);
+
+        finish(errors, settings)
+    }
+}
+
+impl DatabaseSettings {
+    /// 設定ファイルと環境変数からデータベースの項目のみを読み込む.
+    ///
+    /// migrationのように、データベース以外の項目が未設定の環境でも実行する処理で使う.
+    pub fn load() -> Result<DatabaseSettings, ConfigError> {
+        let (path, text) = read_file()?;
+        DatabaseSettings::from_sources(&path, text.as_deref(), |key| env::var(key).ok())
+    }
+
+    /// TOMLの内容と環境変数の取得関数から、データベースの項目のみを読み込む.
+    pub fn from_sources<F>(
+        path: &str,
+        text: Option<&str>,
+        env: F,
+    ) -> Result<DatabaseSettings, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut file = parse_file(path, text)?;
+
+        let mut errors = Vec::new();
+        Overrides {
+            env: &env,
+            errors: &mut errors,
+        }
+        .database(&mut file.database);
+        let database = file.database.validate(&mut errors);
+
+        finish(errors, database)
+    }
+}
+
+/// `AT_API_CONFIG`または`config.toml`の内容. 既定のファイルが無い場合は`None`.
+fn read_file() -> Result<(String, Option<String>), ConfigError> {
+    dotenv().ok();
+    let (path, required) = match env::var(CONFIG_PATH_ENV) {
+        Ok(path) => (path, true),
+        Err(_) => (DEFAULT_CONFIG_PATH.to_string(), false),
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok((path, Some(text))),
+        Err(e) if required || e.kind() != std::io::ErrorKind::NotFound => Err(ConfigError::Read {
+            path,
+            message: e.to_string(),
+        }),
+        Err(_) => Ok((path, None)),
+    }
+}
+
+fn parse_file(path: &str, text: Option<&str>) -> Result<FileSettings, ConfigError> {
+    match text {
+        Some(text) => toml::from_str(text).map_err(|e| ConfigError::Parse {
+            path: path.to_string(),
+            message: e.to_string(),
+        }),
+        None => Ok(FileSettings::default()),
+    }
+}
+
+fn finish<T>(errors: Vec<String>, value: T) -> Result<T, ConfigError> {
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(ConfigError::Invalid(errors))
+    }
+}
+
+/// 環境変数による上書き.
+struct Overrides<'a, F> {
+    env: &'a F,
+    errors: &'a mut Vec<String>,
+}
+
+impl<F> Overrides<'_, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn apply(&mut self, file: &mut FileSettings) {
+        self.parse("PORT", &mut file.server.port);
+        self.parse("JSON_LIMIT", &mut file.server.json_limit);
+
+        self.database(&mut file.database);
+
+        self.parse(
+            "RATE_LIMIT_INTERVAL_SECS",
+            &mut file.rate_limit.interval_secs,
+        );
+        self.parse("RATE_LIMIT_MAX_REQUESTS", &mut file.rate_limit.max_requests);
+        self.limits("RATE_LIMIT_API_KEYS", &mut file.rate_limit.api_keys);
+        self.limits("RATE_LIMIT_UIDS", &mut file.rate_limit.uids);
+
+        // 取得元はどちらか一方のみ. 環境変数で指定した場合はファイルの値を使わない
+        if let Some(url) = (self.env)("JWKS_URL") {
+            file.jwt.jwks_url = Some(url);
+            file.jwt.jwks_file = None;
+        } else if let Some(path) = (self.env)("JWKS_FILE") {
+            file.jwt.jwks_file = Some(path);
+            file.jwt.jwks_url = None;
+        }
+        self.string("JWT_ISSUER", &mut file.jwt.issuer);
+        self.string("JWT_AUDIENCE", &mut file.jwt.audience);
+        self.parse("JWT_LEEWAY_SECS", &mut file.jwt.leeway_secs);
+
+        self.string("DEFAULT_TIME_ZONE", &mut file.users.default_time_zone);
+
+        self.parse("PAYROLL_OVERTIME_RATE", &mut file.payroll.overtime_rate);
+        self.parse(
+            "PAYROLL_OVERTIME_OVER_60H_RATE",
+            &mut file.payroll.overtime_over_60h_rate,
+        );
+        self.parse("PAYROLL_HOLIDAY_RATE", &mut file.payroll.holiday_rate);
+        self.parse("PAYROLL_LATE_NIGHT_RATE", &mut file.payroll.late_night_rate);
+    }
+
+    fn database(&mut self, database: &mut FileDatabase) {
+        self.string("DATABASE_URL", &mut database.url);
+        self.parse("DATABASE_POOL_MAX_SIZE", &mut database.pool_max_size);
+        self.parse("DATABASE_POOL_MIN_IDLE", &mut database.pool_min_idle);
+        self.parse(
+            "DATABASE_POOL_TIMEOUT_SECS",
+            &mut database.pool_timeout_secs,
+        );
+        self.parse("RUN_MIGRATIONS", &mut database.run_migrations);
+    }
+
+    fn string(&mut self, key: &str, target: &mut Option<String>) {
+        if let Some(value) = (self.env)(key) {
+            *target = Some(value);
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, key: &str, target: &mut Option<T>) {
+        if let Some(value) = (self.env)(key) {
+            match value.trim().parse() {
+                Ok(value) => *target = Some(value),
+                Err(_) => self
+                    .errors
+                    .push(format!("{} has an invalid value: {}", key, value)),
+            }
+        }
+    }
+
+    fn limits(&mut self, key: &str, target: &mut HashMap<String, u32>) {
+        if let Some(value) = (self.env)(key) {
+            match parse_limits(&value) {
+                Ok(limits) => *target = limits,
+                Err(message) => self.errors.push(format!("{} {}", key, message)),
+            }
+        }
+    }
+}
+
+/// `a=10,b=20`形式の上限の一覧を読み込む.
+fn parse_limits(value: &str) -> Result<HashMap<String, u32>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let mut parts = item.splitn(2, '=');
+            match (parts.next(), parts.next().map(str::parse::<u32>)) {
+                (Some(id), Some(Ok(limit))) if !id.is_empty() => Ok((id.to_string(), limit)),
+                _ => Err(format!("must be a list of id=limit: {}", item)),
+            }
+        })
+        .collect()
+}
+
+impl FileSettings {
+    /// 値を検証する. エラーは`errors`に追加し、不正な項目は初期値で埋める.
+    fn validate(self, errors: &mut Vec<String>) -> Settings {
+        let database = self.database.validate(errors);
+        let mut check = |valid: bool, message: &str| {
+            if !valid {
+                errors.push(message.to_string());
+            }
+        };
+
+        let port = self.server.port.unwrap_or(8088);
+        check(port > 0, "server.port must be greater than 0");
+        let json_limit = self.server.json_limit.unwrap_or(4096);
+        check(json_limit > 0, "server.json_limit must be greater than 0");
+
+        let default_rate_limit = RateLimitConfig::default();
+        let mut api_keys = HashMap::new();
+        for (id, limit) in self.rate_limit.api_keys {
+            match id.parse::<i32>() {
+                Ok(id) => {
+                    api_keys.insert(id, limit);
+                }
+                Err(_) => check(
+                    false,
+                    &format!("rate_limit.api_keys id must be a number: {}", id),
+                ),
+            }
+        }
+        let rate_limit = RateLimitConfig {
+            interval: self
+                .rate_limit
+                .interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(default_rate_limit.interval),
+            max_requests: self
+                .rate_limit
+                .max_requests
+                .unwrap_or(default_rate_limit.max_requests),
+            api_keys,
+            uids: self.rate_limit.uids,
+        };
+        check(
+            rate_limit.interval > Duration::from_secs(0),
+            "rate_limit.interval_secs must be greater than 0",
+        );
+        check(
+            rate_limit.max_requests > 0,
+            "rate_limit.max_requests must be greater than 0",
+        );
+
+        let source = match (self.jwt.jwks_url, self.jwt.jwks_file) {
+            (Some(url), None) => JwksSource::Url(url),
+            (None, Some(path)) => JwksSource::File(path),
+            (Some(_), Some(_)) => {
+                check(false, "jwt.jwks_url and jwt.jwks_file are exclusive");
+                JwksSource::File(String::new())
+            }
+            (None, None) => {
+                check(
+                    false,
+                    "jwt.jwks_url or jwt.jwks_file must be set (or JWKS_URL, JWKS_FILE)",
+                );
+                JwksSource::File(String::new())
+            }
+        };
+        let issuer = self.jwt.issuer.unwrap_or_default();
+        check(!issuer.is_empty(), "jwt.issuer must be set (or JWT_ISSUER)");
+        let audience = self.jwt.audience.unwrap_or_default();
+        check(
+            !audience.is_empty(),
+            "jwt.audience must be set (or JWT_AUDIENCE)",
+        );
+        let jwt = JwtConfig {
+            source,
+            issuer,
+            audience,
+            leeway_seconds: self.jwt.leeway_secs.unwrap_or(60),
+        };
+
+        let default_time_zone = self
+            .users
+            .default_time_zone
+            .unwrap_or_else(|| "Asia/Tokyo".to_string());
+        if let Err(message) = validate_time_zone(&default_time_zone) {
+            check(false, &format!("users.default_time_zone {}", message));
+        }
+
+        let mut mappings = self.import.mappings;
+        for (name, mapping) in mappings.iter_mut() {
+            check(
+                !BUILTIN_FORMATS.contains(&name.as_str()),
+                &format!("import.mappings.{} conflicts with a built-in format", name),
+            );
+            for message in mapping.validate() {
+                check(false, &format!("import.mappings.{}.{}", name, message));
+            }
+            // users.default_time_zoneは検証済みのため、mappingの検証の後に補う
+            mapping
+                .time_zone
+                .get_or_insert_with(|| default_time_zone.clone());
+        }
+
+        let default_rates = PremiumRates::default();
+        let rates = PremiumRates {
+            overtime: self.payroll.overtime_rate.unwrap_or(default_rates.overtime),
+            overtime_over_60h: self
+                .payroll
+                .overtime_over_60h_rate
+                .unwrap_or(default_rates.overtime_over_60h),
+            holiday: self.payroll.holiday_rate.unwrap_or(default_rates.holiday),
+            late_night: self
+                .payroll
+                .late_night_rate
+                .unwrap_or(default_rates.late_night),
+        };
+        if let Err(message) = rates.validate() {
+            check(false, &format!("payroll: {}", message));
+        }
+
+        Settings {
+            server: ServerSettings { port, json_limit },
+            database,
+            rate_limit,
+            jwt,
+            users: UserSettings { default_time_zone },
+            import: ImportSettings { mappings },
+            payroll: PayrollSettings { rates },
+        }
+    }
+}
+
+impl FileDatabase {
+    fn validate(self, errors: &mut Vec<String>) -> DatabaseSettings {
+        let mut check = |valid: bool, message: &str| {
+            if !valid {
+                errors.push(message.to_string());
+            }
+        };
+
+        let url = self.url.unwrap_or_default();
+        check(
+            !url.is_empty(),
+            "database.url must be set (or DATABASE_URL)",
+        );
+        let default_pool = PoolConfig::default();
+        let pool = PoolConfig {
+            max_size: self.pool_max_size.unwrap_or(default_pool.max_size),
+            min_idle: self.pool_min_idle,
+            connection_timeout: self
+                .pool_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(default_pool.connection_timeout),
+        };
+        check(
+            pool.max_size > 0,
+            "database.pool_max_size must be greater than 0",
+        );
+        check(
+            pool.min_idle.unwrap_or(0) <= pool.max_size,
+            "database.pool_min_idle must not exceed database.pool_max_size",
+        );
+
+        DatabaseSettings {
+            url,
+            pool,
+            run_migrations: self.run_migrations.unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_util {
+    use super::*;
+    use crate::auth::jwt;
+    use crate::database_utils::pool::env_database_url;
+
+    /// テスト用の設定.
+    pub fn settings() -> Settings {
+        Settings {
+            server: ServerSettings {
+                port: 8088,
+                json_limit: 4096,
+            },
+            database: DatabaseSettings {
+                url: env_database_url(),
+                pool: PoolConfig::default(),
+                run_migrations: false,
+            },
+            rate_limit: RateLimitConfig::default(),
+            jwt: jwt::test_util::config(),
+            users: UserSettings {
+                default_time_zone: "Asia/Tokyo".to_string(),
+            },
+            import: ImportSettings::default(),
+            payroll: PayrollSettings::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    const FILE: &str = r#"
+[server]
+port = 9000
+
+[database]
+url = "postgres://file/contents"
+pool_max_size = 20
+
+[rate_limit]
+max_requests = 50
+api_keys = { "1" = 1000 }
+
+[jwt]
+jwks_file = "jwks.json"
+issuer = "https://idp.example.com/"
+audience = "at-api"
+
+[users]
+default_time_zone = "Europe/London"
+
+[payroll]
+overtime_rate = 30
+
+[import.mappings.kintai]
+uid = "社員番号"
+date = "日付"
+start_time = "出勤"
+end_time = "退勤"
+"#;
+
+    #[test]
+    fn from_sources_test() {
+        let settings = Settings::from_sources("config.toml", Some(FILE), env(&[])).unwrap();
+        assert_eq!(settings.server.port, 9000);
+        assert_eq!(settings.server.json_limit, 4096);
+        assert_eq!(settings.database.url, "postgres://file/contents");
+        assert_eq!(settings.database.pool.max_size, 20);
+        assert_eq!(settings.rate_limit.max_requests, 50);
+        assert_eq!(settings.rate_limit.api_keys[&1], 1000);
+        assert!(matches!(settings.jwt.source, JwksSource::File(ref path) if path == "jwks.json"));
+        assert_eq!(settings.users.default_time_zone, "Europe/London");
+        assert_eq!(settings.payroll.rates.overtime, 30);
+        assert_eq!(settings.payroll.rates.holiday, 35);
+        assert_eq!(settings.import.mappings["kintai"].uid, "社員番号");
+        // mappingのtime zoneを省略した場合はusers.default_time_zone
+        assert_eq!(
+            settings.import.mappings["kintai"].time_zone,
+            Some("Europe/London".to_string())
+        );
+
+        // 環境変数で上書きする
+        let vars = [
+            ("PORT", "8000"),
+            ("DATABASE_URL", "postgres://env/contents"),
+            ("RATE_LIMIT_UIDS", "alice=10"),
+            ("JWKS_URL", "https://idp.example.com/jwks.json"),
+            ("RUN_MIGRATIONS", "true"),
+        ];
+        let settings = Settings::from_sources("config.toml", Some(FILE), env(&vars)).unwrap();
+        assert_eq!(settings.server.port, 8000);
+        assert_eq!(settings.database.url, "postgres://env/contents");
+        assert_eq!(settings.database.pool.max_size, 20);
+        assert_eq!(settings.rate_limit.uids["alice"], 10);
+        assert!(matches!(settings.jwt.source, JwksSource::Url(_)));
+        assert!(settings.database.run_migrations);
+
+        // ファイルが無くても環境変数のみで読み込める
+        let vars = [
+            ("DATABASE_URL", "postgres://env/contents"),
+            ("JWKS_FILE", "jwks.json"),
+            ("JWT_ISSUER", "https://idp.example.com/"),
+            ("JWT_AUDIENCE", "at-api"),
+        ];
+        let settings = Settings::from_sources("config.toml", None, env(&vars)).unwrap();
+        assert_eq!(settings.server.port, 8088);
+        assert_eq!(settings.rate_limit.max_requests, 100);
+    }
+
+    #[test]
+    fn invalid_test() {
+        let error = Settings::from_sources("config.toml", Some("[server]\nprot = 1"), env(&[]))
+            .unwrap_err();
+        assert!(matches!(error, ConfigError::Parse { .. }));
+
+        let vars = [
+            ("PORT", "http"),
+            ("DATABASE_POOL_MIN_IDLE", "30"),
+            ("RATE_LIMIT_API_KEYS", "1"),
+            ("DEFAULT_TIME_ZONE", "Mars/Olympus_Mons"),
+            ("PAYROLL_HOLIDAY_RATE", "1000"),
+        ];
+        let error = Settings::from_sources("config.toml", Some(FILE), env(&vars)).unwrap_err();
+        match error {
+            ConfigError::Invalid(errors) => {
+                assert_eq!(errors.len(), 5, "{:?}", errors);
+                assert!(errors[0].starts_with("PORT"));
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+
+        let file = format!(
+            "{}\n[import.mappings.csv]\nuid = \"id\"\nstart_time = \"in\"\nend_time = \"out\"\ntime_zone = \"JST\"",
+            FILE
+        );
+        let error = Settings::from_sources("config.toml", Some(&file), env(&[])).unwrap_err();
+        assert!(error.to_string().contains("import.mappings.csv conflicts"));
+        assert!(error.to_string().contains("import.mappings.csv.time_zone"));
+
+        let error = Settings::from_sources("config.toml", None, env(&[])).unwrap_err();
+        assert!(error.to_string().contains("database.url"));
+        assert!(error.to_string().contains("jwt.jwks_url"));
+        assert!(error.to_string().contains("jwt.issuer"));
+        assert!(error.to_string().contains("jwt.audience"));
+    }
+
+    #[test]
+    fn database_from_sources_test() {
+        // データベース以外の項目は未設定・不正でもよい
+        let vars = [
+            ("DATABASE_URL", "postgres://env/contents"),
+            ("PORT", "http"),
+        ];
+        let database = DatabaseSettings::from_sources("config.toml", None, env(&vars)).unwrap();
+        assert_eq!(database.url, "postgres://env/contents");
+
+        let error = DatabaseSettings::from_sources("config.toml", None, env(&[])).unwrap_err();
+        match error {
+            ConfigError::Invalid(errors) => {
+                assert_eq!(errors.len(), 1, "{:?}", errors);
+                assert!(errors[0].starts_with("database.url"));
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn parse_limits_test() {
+        let limits = parse_limits("1=1000, alice=10,").unwrap();
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits["1"], 1000);
+        assert_eq!(limits["alice"], 10);
+
+        assert!(parse_limits("1").is_err());
+        assert!(parse_limits("=10").is_err());
+        assert!(parse_limits("1=many").is_err());
+    }
+}
@@ -1,19 +1,57 @@
+use crate::usecase::validation::FieldError;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use std::error::Error as StdError;
 use std::fmt;
 
 #[derive(Debug)]
 pub enum DataAccessError {
-    InternalError,
-    InternalErrorWithMessage(String),
+    /// 対象のデータが存在しない.
+    NotFound(String),
+    /// 既存のデータと矛盾するため処理できない.
+    Conflict(String),
+    /// api keyが無い、または無効.
+    Unauthorized(String),
+    /// 他のユーザーのデータを操作しようとした、またはapi keyに権限が無い.
+    Forbidden(String),
+    /// 入力値が不正. 項目毎のエラーを持つ.
+    Validation(Vec<FieldError>),
+    /// データベースに接続できない.
+    Unavailable(String),
+    /// 想定外のエラー. データベースのエラーを原因として保持する.
+    Internal(Option<DieselError>),
 }
 
-impl StdError for DataAccessError {}
+impl DataAccessError {
+    pub fn validation(field: &str, message: &str) -> DataAccessError {
+        DataAccessError::Validation(vec![FieldError::new(field, message)])
+    }
+}
+
+impl StdError for DataAccessError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            DataAccessError::Internal(Some(error)) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for DataAccessError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            DataAccessError::InternalError => write!(f, "data access Error in use case!"),
-            DataAccessError::InternalErrorWithMessage(message) => write!(f, "{}", message),
+            DataAccessError::NotFound(message)
+            | DataAccessError::Conflict(message)
+            | DataAccessError::Unauthorized(message)
+            | DataAccessError::Forbidden(message)
+            | DataAccessError::Unavailable(message) => write!(f, "{}", message),
+            DataAccessError::Validation(errors) => {
+                let messages: Vec<String> = errors
+                    .iter()
+                    .map(|error| format!("{} {}", error.field, error.message))
+                    .collect();
+                write!(f, "Invalid input: {}", messages.join(", "))
+            }
+            DataAccessError::Internal(_) => write!(f, "data access Error in use case!"),
         }
     }
 }
@@ -21,11 +59,40 @@ impl fmt::Display for DataAccessError {
 pub trait UseCase {
     fn parse_data_access_result<T>(
         &self,
-        result: Result<T, diesel::result::Error>,
+        result: Result<T, DieselError>,
     ) -> Result<T, DataAccessError> {
-        match result {
-            Ok(data) => Ok(data),
-            Err(_) => Err(DataAccessError::InternalError),
+        result.map_err(DataAccessError::from)
+    }
+}
+
+impl From<DieselError> for DataAccessError {
+    fn from(error: DieselError) -> Self {
+        match error {
+            DieselError::NotFound => DataAccessError::NotFound("Not found!".to_string()),
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                DataAccessError::Conflict("Already exists!".to_string())
+            }
+            DieselError::DatabaseError(DatabaseErrorKind::UnableToSendCommand, info) => {
+                DataAccessError::Unavailable(info.message().to_string())
+            }
+            error => DataAccessError::Internal(Some(error)),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_diesel_error_test() {
+        assert!(matches!(
+            DataAccessError::from(DieselError::NotFound),
+            DataAccessError::NotFound(_)
+        ));
+
+        let error = DataAccessError::from(DieselError::RollbackTransaction);
+        assert!(matches!(error, DataAccessError::Internal(Some(_))));
+        assert!(error.source().is_some());
+    }
+}
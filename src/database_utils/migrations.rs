@@ -0,0 +1,149 @@
+//! migrationの実行と適用状況.
+//!
+//! 適用は`embed_migrations!`でバイナリに埋め込んだmigrationで行うため、実行環境にdiesel_cliは不要.
+//! diesel_migrationsは埋め込んだmigrationの取り消しに対応しないため、取り消しと適用状況の確認は
+//! diesel_cliと同じくmigrationsディレクトリを参照する.
+//! 適用履歴の`__diesel_schema_migrations`はdiesel_migrationsが管理する.
+use diesel::pg::PgConnection;
+use diesel::sql_types::Bool;
+use diesel::{Connection, RunQueryDsl};
+use diesel_migrations::{
+    find_migrations_directory, migration_from, migration_paths_in_directory,
+    revert_latest_migration_in_directory, Migration, MigrationConnection, MigrationError,
+    RunMigrationsError,
+};
+use std::collections::HashSet;
+use std::io::Write;
+
+embed_migrations!("migrations");
+
+/// 複数のプロセスが同時にmigrationを実行しないためのadvisory lockのkey.
+const LOCK_KEY: i64 = 0x6174_2d61_7069;
+
+/// 埋め込んだmigrationのうち未適用のものをすべて実行する. 実行したmigrationは`output`に出力する.
+///
+/// 1つのtransactionで実行するため、失敗した場合はすべて取り消す.
+pub fn run_pending(
+    connection: &PgConnection,
+    output: &mut dyn Write,
+) -> Result<(), RunMigrationsError> {
+    connection.transaction(|| {
+        lock(connection)?;
+        embedded_migrations::run_with_output(connection, output)
+    })
+}
+
+/// 最後に適用したmigrationを取り消し、そのバージョンを返す.
+/// 適用済みのmigrationが無い場合は`None`を返す.
+pub fn revert_latest(connection: &PgConnection) -> Result<Option<String>, RunMigrationsError> {
+    connection.transaction(|| {
+        lock(connection)?;
+        match revert_latest_migration_in_directory(connection, &find_migrations_directory()?) {
+            Ok(version) => Ok(Some(version)),
+            Err(RunMigrationsError::MigrationError(MigrationError::NoMigrationRun)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    })
+}
+
+/// migrationと、適用済みか.
+pub type MigrationStatus = (Box<dyn Migration>, bool);
+
+/// migrationsディレクトリのmigration毎の適用状況. バージョン順.
+pub fn status(connection: &PgConnection) -> Result<Vec<MigrationStatus>, RunMigrationsError> {
+    let mut migrations = migration_paths_in_directory(&find_migrations_directory()?)?
+        .into_iter()
+        .map(|entry| migration_from(entry.path()))
+        .collect::<Result<Vec<Box<dyn Migration>>, MigrationError>>()?;
+    migrations.sort_by(|a, b| a.version().cmp(b.version()));
+
+    let applied = applied_versions(connection)?;
+    Ok(migrations
+        .into_iter()
+        .map(|migration| {
+            let is_applied = applied.contains(migration.version());
+            (migration, is_applied)
+        })
+        .collect())
+}
+
+/// 未適用のmigrationのバージョン.
+pub fn pending_versions(connection: &PgConnection) -> Result<Vec<String>, RunMigrationsError> {
+    Ok(status(connection)?
+        .into_iter()
+        .filter(|(_, applied)| !applied)
+        .map(|(migration, _)| migration.version().to_string())
+        .collect())
+}
+
+/// データベースに適用済みのmigrationのバージョン.
+///
+/// 管理テーブルがまだ無い場合は空を返す.
+fn applied_versions(connection: &PgConnection) -> Result<HashSet<String>, RunMigrationsError> {
+    #[derive(QueryableByName)]
+    struct Exists {
+        #[sql_type = "Bool"]
+        exists: bool,
+    }
+
+    let table =
+        diesel::sql_query("SELECT to_regclass('__diesel_schema_migrations') IS NOT NULL AS exists")
+            .get_result::<Exists>(connection)?;
+    if !table.exists {
+        return Ok(HashSet::new());
+    }
+    Ok(connection.previously_run_migration_versions()?)
+}
+
+/// transactionの終了まで他のプロセスのmigrationを待たせる.
+fn lock(connection: &PgConnection) -> Result<(), RunMigrationsError> {
+    diesel::sql_query(format!("SELECT pg_advisory_xact_lock({})", LOCK_KEY))
+        .execute(connection)
+        .map(|_| ())
+        .map_err(RunMigrationsError::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util;
+
+    #[test]
+    fn status_test() {
+        let connection = test_util::connection_init();
+        let status = status(&connection).unwrap();
+        let versions = status
+            .iter()
+            .map(|(migration, _)| migration.version())
+            .collect::<Vec<&str>>();
+        assert_eq!(versions.first(), Some(&"00000000000000"));
+        assert!(versions.contains(&"20201107100000"));
+        assert!(status.iter().all(|(_, applied)| *applied));
+        assert_eq!(pending_versions(&connection).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn revert_and_run_test() {
+        // connection_initのtransactionはrollbackされるため、スキーマの変更は残らない
+        let connection = test_util::connection_init();
+        let latest = status(&connection)
+            .unwrap()
+            .last()
+            .map(|(migration, _)| migration.version().to_string())
+            .unwrap();
+
+        let reverted = revert_latest(&connection).unwrap().unwrap();
+        assert_eq!(reverted, latest);
+        assert_eq!(pending_versions(&connection).unwrap(), vec![latest.clone()]);
+
+        // 埋め込んだmigrationで再適用する
+        let mut output = Vec::new();
+        run_pending(&connection, &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains(&latest));
+        assert!(pending_versions(&connection).unwrap().is_empty());
+
+        let mut output = Vec::new();
+        run_pending(&connection, &mut output).unwrap();
+        assert!(output.is_empty());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod error;
+pub mod migrations;
 pub mod pool;
+pub mod repository;
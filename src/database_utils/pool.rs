@@ -3,6 +3,7 @@ use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager, CustomizeConnection};
 use dotenv::dotenv;
 use std::env;
+use std::time::Duration;
 
 pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
@@ -11,6 +12,39 @@ pub fn env_database_url() -> String {
     env::var("DATABASE_URL").expect("DATABASE_URL must be set")
 }
 
+/// 接続プールの設定.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    /// 接続を取得できるまで待つ時間. 超えた場合は503を返す
+    pub connection_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_size: 10,
+            min_idle: None,
+            connection_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 接続プールを作成する.
+///
+/// 起動時にデータベースに接続できなくても失敗せず、接続は利用時に確立する.
+/// 取り出す際に接続を検査するため、データベースの再起動後は自動的に再接続する.
+pub fn build_pool(database_url: &str, config: &PoolConfig) -> DbPool {
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    r2d2::Pool::builder()
+        .max_size(config.max_size)
+        .min_idle(config.min_idle)
+        .connection_timeout(config.connection_timeout)
+        .test_on_check_out(true)
+        .build_unchecked(manager)
+}
+
 /// テスト時にCommitしないtransactionを提供するtrait.
 ///
 /// # example
@@ -50,10 +84,12 @@ pub mod test_util {
         db
     }
 
+    /// すべてのリクエストが同じtransactionを使用するよう、接続は1つとする.
     pub fn setup_connection_pool() -> DbPool {
         let manager = ConnectionManager::<PgConnection>::new(env_database_url());
         r2d2::Pool::builder()
             .connection_customizer(Box::new(TestTransaction))
+            .max_size(1)
             .build(manager)
             .expect("Failed to init pool")
     }
@@ -0,0 +1,197 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::metrics;
+use actix_web::error::BlockingError;
+use actix_web::web;
+use diesel::pg::PgConnection;
+
+/// ドライバーの同期的な処理を、actixのworker threadを塞がないように
+/// ブロッキング処理用のthread poolで実行する.
+///
+/// # example
+///
+/// ```ignore
+/// let record = Repository::new(&pool)
+///     .run(move |connection| clock_in::execute(AttendanceRecordDriver::new(connection), input))
+///     .await?;
+/// ```
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl Repository {
+    pub fn new(pool: &DbPool) -> Repository {
+        Repository { pool: pool.clone() }
+    }
+
+    pub async fn run<F, T>(&self, f: F) -> Result<T, DataAccessError>
+    where
+        F: FnOnce(&PgConnection) -> Result<T, DataAccessError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = self.pool.clone();
+        web::block(move || {
+            let timer = metrics::DB_POOL_WAIT_DURATION.start_timer();
+            let connection = pool.get().map_err(|e| {
+                metrics::DB_POOL_TIMEOUTS.inc();
+                DataAccessError::Unavailable(e.to_string())
+            })?;
+            timer.observe_duration();
+            f(&connection)
+        })
+        .await
+        .map_err(|e| match e {
+            BlockingError::Error(e) => e,
+            BlockingError::Canceled => {
+                DataAccessError::Unavailable("Blocking operation was canceled!".to_string())
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::{build_pool, env_database_url, PoolConfig};
+    use actix_web::dev::Service;
+    use actix_web::{test, App, HttpResponse};
+    use diesel::r2d2::{self, ConnectionManager};
+    use diesel::{Connection, RunQueryDsl};
+    use std::time::{Duration, Instant};
+
+    const SLEEP_SECONDS: f64 = 0.5;
+
+    async fn slow_route(pool: web::Data<DbPool>) -> Result<HttpResponse, DataAccessError> {
+        Repository::new(&pool)
+            .run(|connection| {
+                diesel::sql_query(format!("SELECT pg_sleep({})", SLEEP_SECONDS))
+                    .execute(connection)
+                    .map_err(DataAccessError::from)
+            })
+            .await?;
+
+        Ok(HttpResponse::Ok().finish())
+    }
+
+    /// 遅いクエリを実行するリクエストが並行して処理されるか.
+    #[actix_rt::test]
+    async fn concurrent_requests_test() {
+        let manager = ConnectionManager::<PgConnection>::new(env_database_url());
+        let pool = r2d2::Pool::builder()
+            .max_size(2)
+            .build(manager)
+            .expect("Failed to init pool");
+        let mut app = test::init_service(
+            App::new()
+                .data(pool)
+                .route("/slow", web::get().to(slow_route)),
+        )
+        .await;
+
+        let started = Instant::now();
+        let first = app.call(test::TestRequest::get().uri("/slow").to_request());
+        let second = app.call(test::TestRequest::get().uri("/slow").to_request());
+        let (first, second) = futures::future::join(first, second).await;
+        let elapsed = started.elapsed();
+
+        assert!(first.unwrap().status().is_success());
+        assert!(second.unwrap().status().is_success());
+        // 直列に実行された場合は2倍以上かかる
+        assert!(
+            elapsed < Duration::from_secs_f64(SLEEP_SECONDS * 2.0),
+            "requests were serialized: {:?}",
+            elapsed
+        );
+    }
+
+    async fn ping_route(pool: web::Data<DbPool>) -> Result<HttpResponse, DataAccessError> {
+        Repository::new(&pool)
+            .run(|connection| {
+                diesel::sql_query("SELECT 1")
+                    .execute(connection)
+                    .map_err(DataAccessError::from)
+            })
+            .await?;
+
+        Ok(HttpResponse::Ok().finish())
+    }
+
+    fn single_connection_pool(database_url: &str) -> DbPool {
+        build_pool(
+            database_url,
+            &PoolConfig {
+                max_size: 1,
+                min_idle: Some(0),
+                connection_timeout: Duration::from_millis(200),
+            },
+        )
+    }
+
+    /// 接続をすべて使用中の場合は503を返し、解放後は処理を再開する.
+    #[actix_rt::test]
+    async fn pool_exhausted_test() {
+        let pool = single_connection_pool(&env_database_url());
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .route("/ping", web::get().to(ping_route)),
+        )
+        .await;
+
+        let connection = pool.get().unwrap();
+        let req = test::TestRequest::get().uri("/ping").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 503);
+        assert_eq!(resp.headers().get("retry-after").unwrap(), "5");
+
+        drop(connection);
+        let req = test::TestRequest::get().uri("/ping").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    /// データベース側で切断された接続は破棄し、新しい接続で処理する.
+    #[actix_rt::test]
+    async fn reconnect_test() {
+        let database_url = env_database_url();
+        let pool = single_connection_pool(&database_url);
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .route("/ping", web::get().to(ping_route)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/ping").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        // データベースの再起動を想定し、プール内の接続を強制的に切断する
+        #[derive(QueryableByName)]
+        struct Pid {
+            #[sql_type = "diesel::sql_types::Integer"]
+            pid: i32,
+        }
+        let pid = diesel::sql_query("SELECT pg_backend_pid() AS pid")
+            .get_result::<Pid>(&pool.get().unwrap())
+            .unwrap()
+            .pid;
+        let admin = PgConnection::establish(&database_url).unwrap();
+        diesel::sql_query(format!("SELECT pg_terminate_backend({})", pid))
+            .execute(&admin)
+            .unwrap();
+
+        let req = test::TestRequest::get().uri("/ping").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    #[actix_rt::test]
+    async fn unavailable_test() {
+        // 接続できないデータベース
+        let pool = single_connection_pool("postgres://localhost:1/none");
+
+        let result = Repository::new(&pool).run(|_| Ok(())).await;
+        assert!(matches!(result, Err(DataAccessError::Unavailable(_))));
+    }
+}
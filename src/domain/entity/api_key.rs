@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 発行する鍵の先頭に付ける文字列.
+pub const KEY_PREFIX: &str = "atk_";
+
+/// 鍵のランダムな部分の長さ.
+const KEY_RANDOM_LENGTH: usize = 40;
+
+/// 一覧で鍵を識別するために保存する先頭部分の長さ.
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+/// api keyに許可する操作.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "records:read")]
+    RecordsRead,
+    #[serde(rename = "records:write")]
+    RecordsWrite,
+    #[serde(rename = "users:admin")]
+    UsersAdmin,
+    #[serde(rename = "keys:admin")]
+    KeysAdmin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [
+        Scope::RecordsRead,
+        Scope::RecordsWrite,
+        Scope::UsersAdmin,
+        Scope::KeysAdmin,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::RecordsRead => "records:read",
+            Scope::RecordsWrite => "records:write",
+            Scope::UsersAdmin => "users:admin",
+            Scope::KeysAdmin => "keys:admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Scope, String> {
+        Scope::ALL
+            .iter()
+            .find(|scope| scope.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("Unknown scope: {}!", s))
+    }
+}
+
+/// api key. 鍵そのものは発行時にのみ返す.
+#[derive(Debug, Clone, Queryable, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    /// 鍵の先頭部分
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|s| s == scope.as_str())
+    }
+
+    /// 失効しておらず、有効期限内か.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        let expired = match self.expires_at {
+            Some(expires_at) => expires_at <= now,
+            None => false,
+        };
+        self.revoked_at.is_none() && !expired
+    }
+}
+
+/// 発行したapi keyと鍵.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// 新しい鍵を生成する.
+pub fn generate_key() -> String {
+    let random: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(KEY_RANDOM_LENGTH)
+        .collect();
+    format!("{}{}", KEY_PREFIX, random)
+}
+
+pub fn display_prefix(key: &str) -> String {
+    key.chars().take(DISPLAY_PREFIX_LENGTH).collect()
+}
+
+/// 保存・照合に使用するhash.
+///
+/// 鍵は十分な長さのランダムな文字列のため、saltやstretchingは行わない.
+pub fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn scope_test() {
+        for scope in Scope::ALL.iter() {
+            assert_eq!(scope.as_str().parse::<Scope>(), Ok(*scope));
+        }
+        assert!("records:delete".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn key_test() {
+        let key = generate_key();
+        assert!(key.starts_with(KEY_PREFIX));
+        assert_eq!(key.len(), KEY_PREFIX.len() + KEY_RANDOM_LENGTH);
+        assert_ne!(key, generate_key());
+        assert_eq!(display_prefix(&key).len(), DISPLAY_PREFIX_LENGTH);
+
+        assert_eq!(
+            hash_key("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn is_active_test() {
+        let now = Utc::now();
+        let mut api_key = ApiKey {
+            id: 1,
+            name: "test".to_string(),
+            prefix: "atk_".to_string(),
+            scopes: vec!["records:read".to_string()],
+            expires_at: None,
+            revoked_at: None,
+            created_at: now,
+        };
+        assert!(api_key.is_active(now));
+        assert!(api_key.has_scope(Scope::RecordsRead));
+        assert!(!api_key.has_scope(Scope::RecordsWrite));
+
+        api_key.expires_at = Some(now + Duration::hours(1));
+        assert!(api_key.is_active(now));
+        assert!(!api_key.is_active(now + Duration::hours(1)));
+
+        api_key.expires_at = None;
+        api_key.revoked_at = Some(now);
+        assert!(!api_key.is_active(now));
+    }
+}
@@ -1,16 +1,164 @@
-use chrono::naive::serde::ts_seconds::{deserialize, serialize};
-use chrono::NaiveDateTime;
+use crate::domain::entity::break_period::BreakPeriod;
+use crate::domain::entity::working_time::WorkingTime;
+use chrono::serde::{ts_seconds, ts_seconds_option};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AttendanceRecord {
     pub id: i32,
     pub user_id: i32,
-    #[serde(serialize_with = "serialize")]
-    #[serde(deserialize_with = "deserialize")]
-    pub start_time: NaiveDateTime,
-    #[serde(serialize_with = "serialize")]
-    #[serde(deserialize_with = "deserialize")]
-    pub end_time: NaiveDateTime,
-    pub break_time: i32,
+    #[serde(with = "ts_seconds")]
+    pub start_time: DateTime<Utc>,
+    #[serde(with = "ts_seconds_option")]
+    pub end_time: Option<DateTime<Utc>>,
+    pub breaks: Vec<BreakPeriod>,
+    /// 終了済みの休憩時間の合計(秒).
+    pub break_seconds: i64,
+    /// 備考
+    #[serde(default)]
+    pub note: Option<String>,
+    /// 労働時間の区分. 退勤前のレコードはNone.
+    #[serde(default)]
+    pub working_time: Option<WorkingTime>,
+}
+
+impl AttendanceRecord {
+    pub fn new(
+        id: i32,
+        user_id: i32,
+        start_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        breaks: Vec<BreakPeriod>,
+    ) -> AttendanceRecord {
+        let break_seconds = breaks.iter().map(|item| item.seconds()).sum();
+
+        AttendanceRecord {
+            id,
+            user_id,
+            start_time,
+            end_time,
+            breaks,
+            break_seconds,
+            note: None,
+            working_time: None,
+        }
+    }
+
+    /// 出勤済みで、まだ退勤していない状態か.
+    pub fn is_open(&self) -> bool {
+        self.end_time.is_none()
+    }
+
+    /// 休憩中(終了していない休憩がある状態)か.
+    pub fn is_on_break(&self) -> bool {
+        self.breaks.iter().any(|item| item.is_open())
+    }
+}
+
+/// 休憩がすべて勤務時間内に収まり、互いに重複していないかを検証する.
+/// 退勤前のレコードは、休憩の終了時刻のみを出勤時刻と比較する.
+pub fn validate_breaks(
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    breaks: &[(DateTime<Utc>, Option<DateTime<Utc>>)],
+) -> Result<(), String> {
+    let mut sorted = breaks.to_vec();
+    sorted.sort_by_key(|(break_start, _)| *break_start);
+
+    // 直前の休憩の終了時刻. 休憩中(終了時刻なし)の場合はSome(None)
+    let mut previous_end: Option<Option<DateTime<Utc>>> = None;
+    for (break_start, break_end) in sorted {
+        if break_start < start_time {
+            return Err("Break must start after the start time!".to_string());
+        }
+        if let Some(break_end) = break_end {
+            if break_end < break_start {
+                return Err("Break must end after it starts!".to_string());
+            }
+            match end_time {
+                Some(end_time) if break_end > end_time => {
+                    return Err("Break must end before the end time!".to_string());
+                }
+                _ => {}
+            }
+        } else if end_time.is_some() {
+            return Err("Break is not finished!".to_string());
+        }
+        let overlapped = match previous_end {
+            Some(Some(previous_end)) => break_start < previous_end,
+            Some(None) => true,
+            None => false,
+        };
+        if overlapped {
+            return Err("Breaks must not overlap!".to_string());
+        }
+        previous_end = Some(break_end);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.ymd(2020, 10, 1).and_hms(hour, min, 0)
+    }
+
+    #[test]
+    fn validate_breaks_test() {
+        let start = time(9, 0);
+        let end = Some(time(18, 0));
+
+        assert!(validate_breaks(start, end, &[]).is_ok());
+        assert!(validate_breaks(
+            start,
+            end,
+            &[
+                (time(12, 0), Some(time(13, 0))),
+                (time(15, 0), Some(time(15, 15)))
+            ]
+        )
+        .is_ok());
+        // 勤務時間外
+        assert!(validate_breaks(start, end, &[(time(8, 0), Some(time(9, 30)))]).is_err());
+        assert!(validate_breaks(start, end, &[(time(17, 30), Some(time(18, 30)))]).is_err());
+        // 開始と終了が逆転
+        assert!(validate_breaks(start, end, &[(time(13, 0), Some(time(12, 0)))]).is_err());
+        // 重複
+        assert!(validate_breaks(
+            start,
+            end,
+            &[
+                (time(12, 0), Some(time(13, 0))),
+                (time(12, 30), Some(time(13, 30)))
+            ]
+        )
+        .is_err());
+        // 退勤済みのレコードに終了していない休憩
+        assert!(validate_breaks(start, end, &[(time(12, 0), None)]).is_err());
+    }
+
+    #[test]
+    fn validate_breaks_open_record_test() {
+        let start = time(9, 0);
+
+        assert!(validate_breaks(start, None, &[(time(12, 0), None)]).is_ok());
+        assert!(validate_breaks(
+            start,
+            None,
+            &[(time(10, 0), Some(time(10, 15))), (time(12, 0), None)]
+        )
+        .is_ok());
+        // 休憩中に次の休憩
+        assert!(validate_breaks(
+            start,
+            None,
+            &[(time(12, 0), None), (time(13, 0), Some(time(13, 15)))]
+        )
+        .is_err());
+    }
 }
@@ -0,0 +1,28 @@
+use chrono::serde::{ts_seconds, ts_seconds_option};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BreakPeriod {
+    pub id: i32,
+    pub record_id: i32,
+    #[serde(with = "ts_seconds")]
+    pub start_time: DateTime<Utc>,
+    #[serde(with = "ts_seconds_option")]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl BreakPeriod {
+    /// 休憩開始済みで、まだ終了していない状態か.
+    pub fn is_open(&self) -> bool {
+        self.end_time.is_none()
+    }
+
+    /// 休憩時間(秒). 終了していない休憩は0とする.
+    pub fn seconds(&self) -> i64 {
+        match self.end_time {
+            Some(end_time) => (end_time - self.start_time).num_seconds(),
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Queryable, Serialize, Deserialize)]
+pub struct HourlyWage {
+    pub id: i32,
+    pub user_id: i32,
+    pub amount: i32,
+    /// 適用開始日. 次の適用開始日の前日まで適用する
+    pub effective_from: NaiveDate,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// データベースの制約では検出できない不整合.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    /// 検出したチェックの名前
+    pub check: String,
+    pub uid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<i32>,
+    pub detail: String,
+}
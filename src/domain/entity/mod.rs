@@ -1,2 +1,10 @@
+pub mod api_key;
 pub mod attendance_record;
+pub mod break_period;
+pub mod hourly_wage;
+pub mod integrity_issue;
+pub mod payroll;
+pub mod record_page;
 pub mod user;
+pub mod work_summary;
+pub mod working_time;
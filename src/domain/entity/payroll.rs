@@ -0,0 +1,427 @@
+//! 労働時間の区分と時給から、期間の支給額(割増賃金を含む)を計算する.
+use crate::domain::entity::working_time::WorkingTime;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 割増率(%).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PremiumRates {
+    /// 法定時間外労働
+    pub overtime: i64,
+    /// 月60時間を超える法定時間外労働
+    pub overtime_over_60h: i64,
+    /// 法定休日労働
+    pub holiday: i64,
+    /// 深夜労働
+    pub late_night: i64,
+}
+
+impl Default for PremiumRates {
+    /// 労働基準法第37条に定める最低の割増率.
+    fn default() -> Self {
+        PremiumRates {
+            overtime: 25,
+            overtime_over_60h: 50,
+            holiday: 35,
+            late_night: 25,
+        }
+    }
+}
+
+/// 割増率の上限(%).
+pub const MAX_PREMIUM_RATE: i64 = 200;
+
+impl PremiumRates {
+    /// 法定の割増率を下回らず、`MAX_PREMIUM_RATE`を超えていないか.
+    pub fn validate(&self) -> Result<(), String> {
+        let minimum = PremiumRates::default();
+        if self.overtime < minimum.overtime
+            || self.overtime_over_60h < minimum.overtime_over_60h
+            || self.holiday < minimum.holiday
+            || self.late_night < minimum.late_night
+        {
+            return Err("Premium rates must not be lower than the statutory rates!".to_string());
+        }
+        if [
+            self.overtime,
+            self.overtime_over_60h,
+            self.holiday,
+            self.late_night,
+        ]
+        .iter()
+        .any(|rate| *rate > MAX_PREMIUM_RATE)
+        {
+            return Err(format!(
+                "Premium rates must not be higher than {}%!",
+                MAX_PREMIUM_RATE
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// 月60時間を超える時間外労働の閾値(分).
+const OVERTIME_60H_MINUTES: i64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayKind {
+    /// 実労働時間に対する通常の賃金
+    Base,
+    Overtime,
+    OvertimeOver60h,
+    Holiday,
+    LateNight,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PayrollItem {
+    pub kind: PayKind,
+    pub hourly_wage: i64,
+    pub minutes: i64,
+    /// 時給に対する支給率(%). 割増分のみを計上するため、基本給以外は割増率となる
+    pub rate: i64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Payroll {
+    pub gross_amount: i64,
+    pub items: Vec<PayrollItem>,
+}
+
+/// 計算対象の勤務.
+pub struct PayrollEntry {
+    pub start_time: NaiveDateTime,
+    pub working_time: WorkingTime,
+    pub hourly_wage: i64,
+    /// falseの場合、月60時間の判定にのみ使用し支給額には含めない
+    pub in_period: bool,
+}
+
+/// 支給額を計算する.
+///
+/// 月60時間の判定は出勤日の暦月で行い、期間外(`in_period`がfalse)の時間外労働も計上する.
+/// 金額は区分・時給毎に合計した時間から算出し、1円未満を四捨五入する.
+/// 金額がi64に収まらない場合はエラーとする.
+pub fn calculate(entries: &[PayrollEntry], rates: &PremiumRates) -> Result<Payroll, String> {
+    let mut sorted: Vec<&PayrollEntry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| entry.start_time);
+
+    let mut monthly_overtime: HashMap<(i32, u32), i64> = HashMap::new();
+    let mut minutes: HashMap<(PayKind, i64), i64> = HashMap::new();
+
+    for entry in sorted {
+        let date = entry.start_time.date();
+        let month_total = monthly_overtime
+            .entry((date.year(), date.month()))
+            .or_insert(0);
+        let under_60h = entry
+            .working_time
+            .overtime_minutes
+            .min((OVERTIME_60H_MINUTES - *month_total).max(0));
+        let over_60h = entry.working_time.overtime_minutes - under_60h;
+        *month_total += entry.working_time.overtime_minutes;
+
+        if !entry.in_period {
+            continue;
+        }
+
+        let wage = entry.hourly_wage;
+        let working_time = &entry.working_time;
+        for (kind, value) in [
+            (PayKind::Base, working_time.worked_minutes),
+            (PayKind::Overtime, under_60h),
+            (PayKind::OvertimeOver60h, over_60h),
+            (PayKind::Holiday, working_time.holiday_minutes),
+            (PayKind::LateNight, working_time.late_night_minutes),
+        ]
+        .iter()
+        {
+            if *value > 0 {
+                *minutes.entry((*kind, wage)).or_insert(0) += value;
+            }
+        }
+    }
+
+    let mut items = minutes
+        .into_iter()
+        .map(|((kind, hourly_wage), minutes)| {
+            let rate = match kind {
+                PayKind::Base => 100,
+                PayKind::Overtime => rates.overtime,
+                PayKind::OvertimeOver60h => rates.overtime_over_60h,
+                PayKind::Holiday => rates.holiday,
+                PayKind::LateNight => rates.late_night,
+            };
+            let amount = hourly_wage
+                .checked_mul(minutes)
+                .and_then(|value| value.checked_mul(rate))
+                .and_then(round_amount)
+                .ok_or_else(overflow)?;
+            Ok(PayrollItem {
+                kind,
+                hourly_wage,
+                minutes,
+                rate,
+                amount,
+            })
+        })
+        .collect::<Result<Vec<PayrollItem>, String>>()?;
+    items.sort_by_key(|item| (item.kind, item.hourly_wage));
+
+    let gross_amount = items
+        .iter()
+        .try_fold(0i64, |total, item| total.checked_add(item.amount))
+        .ok_or_else(overflow)?;
+    Ok(Payroll {
+        gross_amount,
+        items,
+    })
+}
+
+fn overflow() -> String {
+    "Payroll amount is too large to calculate!".to_string()
+}
+
+/// 時給×分×% を円に換算し、1円未満を四捨五入する.
+fn round_amount(value: i64) -> Option<i64> {
+    value.checked_add(3000).map(|value| value / 6000)
+}
+
+/// 勤務日に適用される時給. `wages`は(適用開始日, 時給).
+pub fn wage_at(wages: &[(NaiveDate, i64)], date: NaiveDate) -> Option<i64> {
+    wages
+        .iter()
+        .filter(|(effective_from, _)| *effective_from <= date)
+        .max_by_key(|(effective_from, _)| *effective_from)
+        .map(|(_, amount)| *amount)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(day: u32, working_time: WorkingTime, hourly_wage: i64) -> PayrollEntry {
+        PayrollEntry {
+            start_time: NaiveDate::from_ymd(2020, 10, day).and_hms(9, 0, 0),
+            working_time,
+            hourly_wage,
+            in_period: true,
+        }
+    }
+
+    fn working_time(worked: i64, overtime: i64, late_night: i64, holiday: i64) -> WorkingTime {
+        WorkingTime {
+            worked_minutes: worked,
+            scheduled_minutes: worked - overtime - holiday,
+            overtime_minutes: overtime,
+            late_night_minutes: late_night,
+            holiday_minutes: holiday,
+        }
+    }
+
+    fn item(kind: PayKind, hourly_wage: i64, minutes: i64, rate: i64, amount: i64) -> PayrollItem {
+        PayrollItem {
+            kind,
+            hourly_wage,
+            minutes,
+            rate,
+            amount,
+        }
+    }
+
+    #[test]
+    fn base_only_test() {
+        let payroll = calculate(
+            &[entry(5, working_time(480, 0, 0, 0), 1000)],
+            &PremiumRates::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            payroll.items,
+            vec![item(PayKind::Base, 1000, 480, 100, 8000)]
+        );
+        assert_eq!(payroll.gross_amount, 8000);
+    }
+
+    #[test]
+    fn premium_test() {
+        // 時間外2時間(うち深夜1時間) + 法定休日8時間
+        let payroll = calculate(
+            &[
+                entry(5, working_time(600, 120, 60, 0), 1200),
+                entry(4, working_time(480, 0, 0, 480), 1200),
+            ],
+            &PremiumRates::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            payroll.items,
+            vec![
+                item(PayKind::Base, 1200, 1080, 100, 21600),
+                item(PayKind::Overtime, 1200, 120, 25, 600),
+                item(PayKind::Holiday, 1200, 480, 35, 3360),
+                item(PayKind::LateNight, 1200, 60, 25, 300),
+            ]
+        );
+        assert_eq!(payroll.gross_amount, 21600 + 600 + 3360 + 300);
+    }
+
+    #[test]
+    fn overtime_over_60h_test() {
+        // 1日3時間の時間外を20日 + 2時間: 60時間を超える2時間が50%
+        let mut entries: Vec<PayrollEntry> = (1..=20)
+            .map(|day| entry(day, working_time(660, 180, 0, 0), 1000))
+            .collect();
+        entries.push(entry(21, working_time(600, 120, 0, 0), 1000));
+        let payroll = calculate(&entries, &PremiumRates::default()).unwrap();
+
+        let overtime = payroll
+            .items
+            .iter()
+            .find(|item| item.kind == PayKind::Overtime)
+            .unwrap();
+        assert_eq!(overtime.minutes, 3600);
+        assert_eq!(overtime.amount, 15000);
+        let over_60h = payroll
+            .items
+            .iter()
+            .find(|item| item.kind == PayKind::OvertimeOver60h)
+            .unwrap();
+        assert_eq!(over_60h.minutes, 120);
+        assert_eq!(over_60h.amount, 1000);
+    }
+
+    #[test]
+    fn overtime_over_60h_out_of_period_test() {
+        // 期間外の時間外労働も月60時間の判定に含める
+        let mut entries: Vec<PayrollEntry> = (1..=20)
+            .map(|day| PayrollEntry {
+                in_period: false,
+                ..entry(day, working_time(660, 180, 0, 0), 1000)
+            })
+            .collect();
+        entries.push(entry(21, working_time(540, 60, 0, 0), 1000));
+        let payroll = calculate(&entries, &PremiumRates::default()).unwrap();
+
+        assert_eq!(
+            payroll.items,
+            vec![
+                item(PayKind::Base, 1000, 540, 100, 9000),
+                item(PayKind::OvertimeOver60h, 1000, 60, 50, 500),
+            ]
+        );
+    }
+
+    #[test]
+    fn overtime_60h_resets_monthly_test() {
+        let mut entries: Vec<PayrollEntry> = (1..=20)
+            .map(|day| entry(day, working_time(660, 180, 0, 0), 1000))
+            .collect();
+        entries.push(PayrollEntry {
+            start_time: NaiveDate::from_ymd(2020, 11, 2).and_hms(9, 0, 0),
+            ..entry(1, working_time(540, 60, 0, 0), 1000)
+        });
+        let payroll = calculate(&entries, &PremiumRates::default()).unwrap();
+
+        let overtime = payroll
+            .items
+            .iter()
+            .find(|item| item.kind == PayKind::Overtime)
+            .unwrap();
+        assert_eq!(overtime.minutes, 3660);
+        assert!(payroll
+            .items
+            .iter()
+            .all(|item| item.kind != PayKind::OvertimeOver60h));
+    }
+
+    #[test]
+    fn wage_change_test() {
+        // 時給の異なる勤務は別の明細とする
+        let payroll = calculate(
+            &[
+                entry(5, working_time(480, 0, 0, 0), 1000),
+                entry(6, working_time(480, 0, 0, 0), 1100),
+            ],
+            &PremiumRates::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            payroll.items,
+            vec![
+                item(PayKind::Base, 1000, 480, 100, 8000),
+                item(PayKind::Base, 1100, 480, 100, 8800),
+            ]
+        );
+    }
+
+    #[test]
+    fn rounding_test() {
+        // 1001円 × 1分 = 16.68円 → 17円, 1001円 × 1分 × 25% = 4.17円 → 4円
+        let payroll = calculate(
+            &[entry(5, working_time(1, 1, 0, 0), 1001)],
+            &PremiumRates::default(),
+        )
+        .unwrap();
+
+        assert_eq!(payroll.items[0].amount, 17);
+        assert_eq!(payroll.items[1].amount, 4);
+    }
+
+    #[test]
+    fn custom_rates_test() {
+        let rates = PremiumRates {
+            overtime: 30,
+            ..PremiumRates::default()
+        };
+        assert!(rates.validate().is_ok());
+        let payroll = calculate(&[entry(5, working_time(540, 60, 0, 0), 1000)], &rates).unwrap();
+        assert_eq!(payroll.items[1], item(PayKind::Overtime, 1000, 60, 30, 300));
+
+        let rates = PremiumRates {
+            holiday: 25,
+            ..PremiumRates::default()
+        };
+        assert!(rates.validate().is_err());
+
+        let rates = PremiumRates {
+            late_night: MAX_PREMIUM_RATE + 1,
+            ..PremiumRates::default()
+        };
+        assert!(rates.validate().is_err());
+    }
+
+    #[test]
+    fn overflow_test() {
+        let result = calculate(
+            &[entry(5, working_time(480, 0, 0, 0), i64::MAX / 100)],
+            &PremiumRates::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn wage_at_test() {
+        let wages = vec![
+            (NaiveDate::from_ymd(2020, 4, 1), 1000),
+            (NaiveDate::from_ymd(2020, 10, 1), 1100),
+        ];
+
+        assert_eq!(wage_at(&wages, NaiveDate::from_ymd(2020, 3, 31)), None);
+        assert_eq!(
+            wage_at(&wages, NaiveDate::from_ymd(2020, 9, 30)),
+            Some(1000)
+        );
+        assert_eq!(
+            wage_at(&wages, NaiveDate::from_ymd(2020, 10, 1)),
+            Some(1100)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 検索結果の1ページ分のレコード.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordPage {
+    pub records: Vec<AttendanceRecord>,
+    /// 次のページを取得するためのカーソル. 出勤時刻順以外で並び替えた場合はNone
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    /// 条件に一致するレコードの総数. 要求された場合のみ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+/// 出勤時刻とidの組による検索位置. この位置の次のレコードから取得する.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    pub start_time: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl Cursor {
+    pub fn of(record: &AttendanceRecord) -> Cursor {
+        Cursor {
+            start_time: record.start_time,
+            id: record.id,
+        }
+    }
+
+    /// クライアントからは内容を解釈しない文字列として扱う.
+    pub fn encode(&self) -> String {
+        base64::encode_config(
+            format!("{}:{}", self.start_time.timestamp(), self.id),
+            base64::URL_SAFE_NO_PAD,
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Cursor, String> {
+        let invalid = || "Invalid cursor!".to_string();
+        let bytes =
+            base64::decode_config(cursor, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = text.splitn(2, ':');
+        let timestamp = parts
+            .next()
+            .and_then(|part| part.parse::<i64>().ok())
+            .ok_or_else(invalid)?;
+        let id = parts
+            .next()
+            .and_then(|part| part.parse::<i32>().ok())
+            .ok_or_else(invalid)?;
+
+        Ok(Cursor {
+            start_time: Utc
+                .timestamp_opt(timestamp, 0)
+                .single()
+                .ok_or_else(invalid)?,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor_test() {
+        let cursor = Cursor {
+            start_time: Utc.ymd(2020, 10, 1).and_hms(9, 0, 0),
+            id: 42,
+        };
+        let encoded = cursor.encode();
+        assert!(!encoded.contains("42"));
+        assert_eq!(Cursor::decode(&encoded), Ok(cursor));
+
+        assert!(Cursor::decode("").is_err());
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(Cursor::decode(&base64::encode_config(
+            "1601542800",
+            base64::URL_SAFE_NO_PAD
+        ))
+        .is_err());
+    }
+}
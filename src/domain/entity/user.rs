@@ -1,7 +1,35 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+/// time zoneが指定されない場合に使用する.
+pub const DEFAULT_TIME_ZONE: Tz = chrono_tz::Asia::Tokyo;
+
 #[derive(Debug, Queryable, Serialize, Deserialize)]
 pub struct User {
     pub id: i32,
     pub uid: String,
+    /// IANA time zone名(例: Asia/Tokyo)
+    pub time_zone: String,
+    /// 無効化した日時. 無効化したユーザーは勤怠記録を登録・変更できない
+    #[serde(default)]
+    pub deactivated_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    /// 日付・週・月の境界の判定に使用するtime zone.
+    pub fn tz(&self) -> Tz {
+        self.time_zone.parse().unwrap_or(DEFAULT_TIME_ZONE)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+}
+
+pub fn validate_time_zone(time_zone: &str) -> Result<(), String> {
+    time_zone
+        .parse::<Tz>()
+        .map(|_| ())
+        .map_err(|_| format!("Unknown time zone: {}!", time_zone))
 }
@@ -0,0 +1,47 @@
+use crate::domain::entity::working_time::WorkingTime;
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 集計単位(日/週/月)毎の勤務時間.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkSummaryItem {
+    /// 集計単位の開始日時(ユーザーのtime zoneにおける0:00)
+    #[serde(with = "ts_seconds")]
+    pub start: DateTime<Utc>,
+    pub record_count: i64,
+    pub worked_minutes: i64,
+    pub break_minutes: i64,
+    pub working_time: WorkingTime,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkSummaryTotal {
+    pub record_count: i64,
+    pub worked_minutes: i64,
+    pub break_minutes: i64,
+    pub working_time: WorkingTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkSummary {
+    pub items: Vec<WorkSummaryItem>,
+    pub total: WorkSummaryTotal,
+}
+
+impl WorkSummary {
+    pub fn new(items: Vec<WorkSummaryItem>) -> WorkSummary {
+        let total = items
+            .iter()
+            .fold(WorkSummaryTotal::default(), |total, item| {
+                WorkSummaryTotal {
+                    record_count: total.record_count + item.record_count,
+                    worked_minutes: total.worked_minutes + item.worked_minutes,
+                    break_minutes: total.break_minutes + item.break_minutes,
+                    working_time: total.working_time + item.working_time,
+                }
+            });
+
+        WorkSummary { items, total }
+    }
+}
@@ -0,0 +1,623 @@
+//! 労働基準法に基づく労働時間の区分.
+//!
+//! - 法定労働時間: 1日8時間・1週40時間(第32条)
+//! - 法定時間外労働: 法定労働時間を超える労働. 日単位で超過した時間は週の計算に含めない
+//! - 深夜労働: 22:00〜翌5:00の労働(第37条4項). 他の区分と重複して計上する
+//! - 法定休日労働: 法定休日(暦日0:00〜24:00)の労働. 時間外労働の計算に含めない
+//!
+//! 日を跨ぐ勤務は始業日の労働として1日8時間を判定する.
+//! 日付・時刻の境界はユーザーのtime zoneで判定し、時間は実経過時間で計上する(夏時間を考慮).
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::{Add, AddAssign};
+
+/// 労働時間の区分毎の集計(分).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WorkingTime {
+    /// 実労働時間(休憩を除く)
+    pub worked_minutes: i64,
+    /// 法定内の労働時間
+    pub scheduled_minutes: i64,
+    /// 法定時間外労働
+    pub overtime_minutes: i64,
+    /// 深夜労働
+    pub late_night_minutes: i64,
+    /// 法定休日労働
+    pub holiday_minutes: i64,
+}
+
+impl Add for WorkingTime {
+    type Output = WorkingTime;
+
+    fn add(self, other: WorkingTime) -> WorkingTime {
+        WorkingTime {
+            worked_minutes: self.worked_minutes + other.worked_minutes,
+            scheduled_minutes: self.scheduled_minutes + other.scheduled_minutes,
+            overtime_minutes: self.overtime_minutes + other.overtime_minutes,
+            late_night_minutes: self.late_night_minutes + other.late_night_minutes,
+            holiday_minutes: self.holiday_minutes + other.holiday_minutes,
+        }
+    }
+}
+
+impl AddAssign for WorkingTime {
+    fn add_assign(&mut self, other: WorkingTime) {
+        *self = *self + other;
+    }
+}
+
+/// 就業規則で定める区分の前提.
+#[derive(Debug, Clone)]
+pub struct LaborRules {
+    /// 法定休日とする曜日
+    pub statutory_holiday: Weekday,
+    /// 週の起算日
+    pub week_start: Weekday,
+    pub daily_limit_minutes: i64,
+    pub weekly_limit_minutes: i64,
+}
+
+impl Default for LaborRules {
+    /// 就業規則に定めがない場合の扱い(日曜日を法定休日・週の起算日とする).
+    fn default() -> Self {
+        LaborRules {
+            statutory_holiday: Weekday::Sun,
+            week_start: Weekday::Sun,
+            daily_limit_minutes: 8 * 60,
+            weekly_limit_minutes: 40 * 60,
+        }
+    }
+}
+
+impl LaborRules {
+    /// 指定日を含む週の起算日.
+    pub fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = (7 + date.weekday().num_days_from_sunday()
+            - self.week_start.num_days_from_sunday())
+            % 7;
+        date - Duration::days(offset as i64)
+    }
+}
+
+#[derive(Default)]
+struct Seconds {
+    worked: i64,
+    overtime: i64,
+    late_night: i64,
+    holiday: i64,
+}
+
+impl Seconds {
+    fn to_minutes(&self) -> WorkingTime {
+        let worked_minutes = self.worked / 60;
+        let overtime_minutes = self.overtime / 60;
+        let holiday_minutes = self.holiday / 60;
+
+        WorkingTime {
+            worked_minutes,
+            scheduled_minutes: worked_minutes - overtime_minutes - holiday_minutes,
+            overtime_minutes,
+            late_night_minutes: self.late_night / 60,
+            holiday_minutes,
+        }
+    }
+}
+
+/// time zoneにおける日付.
+pub fn local_date<Tz: TimeZone>(tz: &Tz, datetime: DateTime<Utc>) -> NaiveDate {
+    datetime.with_timezone(tz).naive_local().date()
+}
+
+/// time zoneにおける日時を実時刻に変換する.
+/// 夏時間の終了で重複する時刻は早い方、開始で存在しない時刻は1時間後の時刻とする.
+pub fn local_instant<Tz: TimeZone>(tz: &Tz, datetime: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&datetime) {
+        LocalResult::Single(datetime) => datetime.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => local_instant(tz, datetime + Duration::hours(1)),
+    }
+}
+
+/// 退勤済みのレコードを区分し、レコードidをキーに返す.
+///
+/// 週40時間の判定は渡されたレコードの中で行うため、
+/// 週の途中からのレコードのみを渡すと時間外労働が少なく計上される.
+pub fn classify<Tz: TimeZone>(
+    records: &[AttendanceRecord],
+    rules: &LaborRules,
+    tz: &Tz,
+) -> HashMap<i32, WorkingTime> {
+    let mut closed: Vec<&AttendanceRecord> = records
+        .iter()
+        .filter(|record| record.end_time.is_some())
+        .collect();
+    closed.sort_by_key(|record| (record.start_time, record.id));
+
+    let daily_limit = rules.daily_limit_minutes * 60;
+    let weekly_limit = rules.weekly_limit_minutes * 60;
+    let mut daily_regular: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut weekly_regular: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut results = HashMap::new();
+
+    for record in closed {
+        let day = local_date(tz, record.start_time);
+        let week = rules.week_of(day);
+        let mut seconds = Seconds::default();
+
+        for (start, end) in working_periods(record) {
+            for (start, end) in split_at_midnight(start, end, tz) {
+                let length = (end - start).num_seconds();
+                seconds.worked += length;
+                seconds.late_night += late_night_seconds(start, end, tz);
+
+                if local_date(tz, start).weekday() == rules.statutory_holiday {
+                    seconds.holiday += length;
+                    continue;
+                }
+
+                let day_total = daily_regular.entry(day).or_insert(0);
+                let week_total = weekly_regular.entry(week).or_insert(0);
+                let allowed = (daily_limit - *day_total)
+                    .min(weekly_limit - *week_total)
+                    .max(0);
+                let regular = length.min(allowed);
+                *day_total += regular;
+                *week_total += regular;
+                seconds.overtime += length - regular;
+            }
+        }
+
+        results.insert(record.id, seconds.to_minutes());
+    }
+
+    results
+}
+
+/// 勤務時間から終了済みの休憩を除いた時間帯.
+fn working_periods(record: &AttendanceRecord) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let end_time = match record.end_time {
+        Some(end_time) => end_time,
+        None => return vec![],
+    };
+    let mut breaks: Vec<(DateTime<Utc>, DateTime<Utc>)> = record
+        .breaks
+        .iter()
+        .filter_map(|item| item.end_time.map(|end| (item.start_time, end)))
+        .collect();
+    breaks.sort();
+
+    let mut periods = vec![];
+    let mut cursor = record.start_time;
+    for (break_start, break_end) in breaks {
+        let break_start = break_start.max(cursor).min(end_time);
+        if cursor < break_start {
+            periods.push((cursor, break_start));
+        }
+        cursor = cursor.max(break_end.min(end_time));
+    }
+    if cursor < end_time {
+        periods.push((cursor, end_time));
+    }
+
+    periods
+}
+
+fn split_at_midnight<Tz: TimeZone>(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut pieces = vec![];
+    let mut cursor = start;
+    while cursor < end {
+        let next_date = local_date(tz, cursor) + Duration::days(1);
+        let next_midnight = local_instant(tz, next_date.and_hms(0, 0, 0));
+        let piece_end = next_midnight.min(end);
+        pieces.push((cursor, piece_end));
+        cursor = piece_end;
+    }
+
+    pieces
+}
+
+/// 22:00〜翌5:00と重なる秒数.
+fn late_night_seconds<Tz: TimeZone>(start: DateTime<Utc>, end: DateTime<Utc>, tz: &Tz) -> i64 {
+    let mut seconds = 0;
+    let mut date = local_date(tz, start) - Duration::days(1);
+    while date <= local_date(tz, end) {
+        let window_start = local_instant(tz, date.and_hms(22, 0, 0));
+        let window_end = local_instant(tz, (date + Duration::days(1)).and_hms(5, 0, 0));
+        let overlap = (end.min(window_end) - start.max(window_start)).num_seconds();
+        if overlap > 0 {
+            seconds += overlap;
+        }
+        date += Duration::days(1);
+    }
+
+    seconds
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::entity::break_period::BreakPeriod;
+    use chrono_tz::{America, Asia};
+
+    /// 2020/10/4は日曜日
+    fn time(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.ymd(2020, 10, 1).and_hms(0, 0, 0)
+            + Duration::days(day as i64 - 1)
+            + Duration::hours(hour as i64)
+            + Duration::minutes(min as i64)
+    }
+
+    fn record(
+        id: i32,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        breaks: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> AttendanceRecord {
+        AttendanceRecord::new(
+            id,
+            1,
+            start_time,
+            Some(end_time),
+            breaks
+                .into_iter()
+                .map(|(start_time, end_time)| BreakPeriod {
+                    id: 0,
+                    record_id: id,
+                    start_time,
+                    end_time: Some(end_time),
+                })
+                .collect(),
+        )
+    }
+
+    fn minutes(
+        worked: i64,
+        scheduled: i64,
+        overtime: i64,
+        late_night: i64,
+        holiday: i64,
+    ) -> WorkingTime {
+        WorkingTime {
+            worked_minutes: worked,
+            scheduled_minutes: scheduled,
+            overtime_minutes: overtime,
+            late_night_minutes: late_night,
+            holiday_minutes: holiday,
+        }
+    }
+
+    #[test]
+    fn scheduled_day_test() {
+        let records = vec![record(
+            1,
+            time(5, 9, 0),
+            time(5, 18, 0),
+            vec![(time(5, 12, 0), time(5, 13, 0))],
+        )];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(480, 480, 0, 0, 0));
+    }
+
+    #[test]
+    fn daily_overtime_test() {
+        let records = vec![record(
+            1,
+            time(5, 9, 0),
+            time(5, 20, 30),
+            vec![(time(5, 12, 0), time(5, 13, 0))],
+        )];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(630, 480, 150, 0, 0));
+    }
+
+    #[test]
+    fn daily_overtime_across_records_test() {
+        // 同じ日の2回目の勤務で8時間を超える
+        let records = vec![
+            record(1, time(5, 8, 0), time(5, 14, 0), vec![]),
+            record(2, time(5, 15, 0), time(5, 19, 0), vec![]),
+        ];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(360, 360, 0, 0, 0));
+        assert_eq!(results[&2], minutes(240, 120, 120, 0, 0));
+    }
+
+    #[test]
+    fn weekly_overtime_test() {
+        // 月〜土に7時間ずつ: 土曜日の2時間が週40時間超
+        let records: Vec<AttendanceRecord> = (5..=10)
+            .map(|day| record(day as i32, time(day, 9, 0), time(day, 16, 0), vec![]))
+            .collect();
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        for day in 5..=9 {
+            assert_eq!(results[&day], minutes(420, 420, 0, 0, 0));
+        }
+        assert_eq!(results[&10], minutes(420, 300, 120, 0, 0));
+    }
+
+    #[test]
+    fn weekly_overtime_excludes_daily_overtime_test() {
+        // 月曜日の2時間は日単位の時間外. 週の計算は月〜金の法定内40時間で上限に達する
+        let mut records = vec![record(5, time(5, 8, 0), time(5, 18, 0), vec![])];
+        for day in 6..=9 {
+            records.push(record(
+                day as i32,
+                time(day, 9, 0),
+                time(day, 17, 0),
+                vec![],
+            ));
+        }
+        records.push(record(10, time(10, 9, 0), time(10, 10, 0), vec![]));
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&5], minutes(600, 480, 120, 0, 0));
+        assert_eq!(results[&9], minutes(480, 480, 0, 0, 0));
+        assert_eq!(results[&10], minutes(60, 0, 60, 0, 0));
+    }
+
+    #[test]
+    fn weekly_limit_resets_on_week_start_test() {
+        // 日曜日起算のため、翌週の月曜日は週40時間の判定がリセットされる
+        let mut records: Vec<AttendanceRecord> = (5..=9)
+            .map(|day| record(day as i32, time(day, 9, 0), time(day, 17, 0), vec![]))
+            .collect();
+        records.push(record(12, time(12, 9, 0), time(12, 17, 0), vec![]));
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&12], minutes(480, 480, 0, 0, 0));
+    }
+
+    #[test]
+    fn late_night_test() {
+        // 20:00〜翌2:00
+        let records = vec![record(1, time(5, 20, 0), time(6, 2, 0), vec![])];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(360, 360, 0, 240, 0));
+    }
+
+    #[test]
+    fn early_morning_late_night_test() {
+        // 4:00〜13:00(休憩1時間)
+        let records = vec![record(
+            1,
+            time(5, 4, 0),
+            time(5, 13, 0),
+            vec![(time(5, 10, 0), time(5, 11, 0))],
+        )];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(480, 480, 0, 60, 0));
+    }
+
+    #[test]
+    fn late_night_overtime_test() {
+        // 13:00〜翌0:00(休憩1時間): 22:00以降は時間外かつ深夜
+        let records = vec![record(
+            1,
+            time(5, 13, 0),
+            time(6, 0, 0),
+            vec![(time(5, 17, 0), time(5, 18, 0))],
+        )];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(600, 480, 120, 120, 0));
+    }
+
+    #[test]
+    fn late_night_break_test() {
+        // 深夜帯の休憩は深夜労働に含めない
+        let records = vec![record(
+            1,
+            time(5, 21, 0),
+            time(6, 6, 0),
+            vec![(time(6, 1, 0), time(6, 2, 0))],
+        )];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(480, 480, 0, 360, 0));
+    }
+
+    #[test]
+    fn holiday_test() {
+        // 法定休日の労働は8時間を超えても時間外とせず、週の計算にも含めない
+        let mut records = vec![record(
+            4,
+            time(4, 9, 0),
+            time(4, 20, 0),
+            vec![(time(4, 12, 0), time(4, 13, 0))],
+        )];
+        for day in 5..=9 {
+            records.push(record(
+                day as i32,
+                time(day, 9, 0),
+                time(day, 17, 0),
+                vec![],
+            ));
+        }
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&4], minutes(600, 0, 0, 0, 600));
+        assert_eq!(results[&9], minutes(480, 480, 0, 0, 0));
+    }
+
+    #[test]
+    fn shift_into_holiday_test() {
+        // 土曜日20:00〜日曜日2:00: 0:00以降が法定休日労働
+        let records = vec![record(1, time(3, 20, 0), time(4, 2, 0), vec![])];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(360, 240, 0, 240, 120));
+    }
+
+    #[test]
+    fn custom_rules_test() {
+        // 土曜日を法定休日、月曜日を週の起算日とする
+        let rules = LaborRules {
+            statutory_holiday: Weekday::Sat,
+            week_start: Weekday::Mon,
+            ..LaborRules::default()
+        };
+        let mut records: Vec<AttendanceRecord> = (5..=9)
+            .map(|day| record(day as i32, time(day, 9, 0), time(day, 17, 0), vec![]))
+            .collect();
+        records.push(record(10, time(10, 9, 0), time(10, 12, 0), vec![]));
+        records.push(record(11, time(11, 9, 0), time(11, 12, 0), vec![]));
+        let results = classify(&records, &rules, &Utc);
+
+        assert_eq!(results[&10], minutes(180, 0, 0, 0, 180));
+        assert_eq!(results[&11], minutes(180, 0, 180, 0, 0));
+    }
+
+    #[test]
+    fn open_record_test() {
+        let records = vec![AttendanceRecord::new(1, 1, time(5, 9, 0), None, vec![])];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn unordered_records_test() {
+        // 渡す順序に関わらず時系列で判定する
+        let records = vec![
+            record(2, time(5, 15, 0), time(5, 19, 0), vec![]),
+            record(1, time(5, 8, 0), time(5, 14, 0), vec![]),
+        ];
+        let results = classify(&records, &LaborRules::default(), &Utc);
+
+        assert_eq!(results[&1], minutes(360, 360, 0, 0, 0));
+        assert_eq!(results[&2], minutes(240, 120, 120, 0, 0));
+    }
+
+    #[test]
+    fn time_zone_test() {
+        // UTC 10/5 23:00〜10/6 8:00 = JST 10/6 8:00〜17:00
+        let records = vec![record(1, time(5, 23, 0), time(6, 8, 0), vec![])];
+
+        let results = classify(&records, &LaborRules::default(), &Utc);
+        assert_eq!(results[&1], minutes(540, 480, 60, 360, 0));
+
+        let results = classify(&records, &LaborRules::default(), &Asia::Tokyo);
+        assert_eq!(results[&1], minutes(540, 480, 60, 0, 0));
+    }
+
+    #[test]
+    fn time_zone_holiday_test() {
+        // UTC 10/3(土) 20:00〜23:00 = JST 10/4(日) 5:00〜8:00
+        let records = vec![record(1, time(3, 20, 0), time(3, 23, 0), vec![])];
+
+        let results = classify(&records, &LaborRules::default(), &Asia::Tokyo);
+        assert_eq!(results[&1], minutes(180, 0, 0, 0, 180));
+    }
+
+    #[test]
+    fn daylight_saving_start_test() {
+        // 2020/3/8(日) 2:00に夏時間開始. 現地0:00〜9:00は実時間8時間
+        let rules = LaborRules {
+            statutory_holiday: Weekday::Sat,
+            ..LaborRules::default()
+        };
+        let start = local_instant(
+            &America::New_York,
+            NaiveDate::from_ymd(2020, 3, 8).and_hms(0, 0, 0),
+        );
+        let end = local_instant(
+            &America::New_York,
+            NaiveDate::from_ymd(2020, 3, 8).and_hms(9, 0, 0),
+        );
+        let records = vec![record(1, start, end, vec![])];
+        let results = classify(&records, &rules, &America::New_York);
+
+        // 深夜労働は現地0:00〜5:00の実時間4時間
+        assert_eq!(results[&1], minutes(480, 480, 0, 240, 0));
+    }
+
+    #[test]
+    fn daylight_saving_end_test() {
+        // 2020/11/1(日) 2:00に夏時間終了. 現地0:00〜8:00は実時間9時間
+        let rules = LaborRules {
+            statutory_holiday: Weekday::Sat,
+            ..LaborRules::default()
+        };
+        let start = local_instant(
+            &America::New_York,
+            NaiveDate::from_ymd(2020, 11, 1).and_hms(0, 0, 0),
+        );
+        let end = local_instant(
+            &America::New_York,
+            NaiveDate::from_ymd(2020, 11, 1).and_hms(8, 0, 0),
+        );
+        let records = vec![record(1, start, end, vec![])];
+        let results = classify(&records, &rules, &America::New_York);
+
+        assert_eq!(results[&1], minutes(540, 480, 60, 360, 0));
+    }
+
+    #[test]
+    fn local_instant_test() {
+        // 存在しない時刻
+        assert_eq!(
+            local_instant(
+                &America::New_York,
+                NaiveDate::from_ymd(2020, 3, 8).and_hms(2, 30, 0)
+            ),
+            Utc.ymd(2020, 3, 8).and_hms(7, 30, 0)
+        );
+        // 重複する時刻
+        assert_eq!(
+            local_instant(
+                &America::New_York,
+                NaiveDate::from_ymd(2020, 11, 1).and_hms(1, 30, 0)
+            ),
+            Utc.ymd(2020, 11, 1).and_hms(5, 30, 0)
+        );
+        assert_eq!(
+            local_date(&Asia::Tokyo, Utc.ymd(2020, 10, 5).and_hms(15, 0, 0)),
+            NaiveDate::from_ymd(2020, 10, 6)
+        );
+    }
+
+    #[test]
+    fn week_of_test() {
+        let rules = LaborRules::default();
+        assert_eq!(
+            rules.week_of(time(4, 0, 0).naive_utc().date()),
+            time(4, 0, 0).naive_utc().date()
+        );
+        assert_eq!(
+            rules.week_of(time(10, 0, 0).naive_utc().date()),
+            time(4, 0, 0).naive_utc().date()
+        );
+
+        let rules = LaborRules {
+            week_start: Weekday::Mon,
+            ..LaborRules::default()
+        };
+        assert_eq!(
+            rules.week_of(time(4, 0, 0).naive_utc().date()),
+            NaiveDate::from_ymd(2020, 9, 28)
+        );
+        assert_eq!(
+            rules.week_of(time(5, 0, 0).naive_utc().date()),
+            time(5, 0, 0).naive_utc().date()
+        );
+    }
+
+    #[test]
+    fn working_time_add_test() {
+        let total = minutes(480, 480, 0, 0, 0) + minutes(600, 480, 120, 60, 0);
+        assert_eq!(total, minutes(1080, 960, 120, 60, 0));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::database_utils::error::{DataAccessError, UseCase};
+use crate::domain::entity::api_key::{
+    display_prefix, generate_key, hash_key, ApiKey, IssuedApiKey, Scope,
+};
+use crate::schema::api_keys::{self, dsl};
+use crate::usecase::api_keys::authenticate::AuthenticateUseCase;
+use crate::usecase::api_keys::issue::IssueApiKeyUseCase;
+use crate::usecase::api_keys::list::ListApiKeysUseCase;
+use crate::usecase::api_keys::revoke::RevokeApiKeyUseCase;
+use crate::usecase::api_keys::rotate::RotateApiKeyUseCase;
+use chrono::{DateTime, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+/// 鍵のhashを除いた列.
+type ApiKeyColumns = (
+    dsl::id,
+    dsl::name,
+    dsl::prefix,
+    dsl::scopes,
+    dsl::expires_at,
+    dsl::revoked_at,
+    dsl::created_at,
+);
+
+const API_KEY_COLUMNS: ApiKeyColumns = (
+    dsl::id,
+    dsl::name,
+    dsl::prefix,
+    dsl::scopes,
+    dsl::expires_at,
+    dsl::revoked_at,
+    dsl::created_at,
+);
+
+#[derive(Insertable)]
+#[table_name = "api_keys"]
+struct NewApiKey {
+    name: String,
+    prefix: String,
+    key_hash: String,
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct ApiKeyDriver<'a> {
+    connection: &'a PgConnection,
+}
+
+impl<'a> ApiKeyDriver<'a> {
+    pub fn new(connection: &'a PgConnection) -> ApiKeyDriver<'a> {
+        ApiKeyDriver { connection }
+    }
+
+    fn insert(
+        &self,
+        name: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<IssuedApiKey, DataAccessError> {
+        let key = generate_key();
+        let new_api_key = NewApiKey {
+            name,
+            prefix: display_prefix(&key),
+            key_hash: hash_key(&key),
+            scopes,
+            expires_at,
+        };
+
+        let result = diesel::insert_into(api_keys::table)
+            .values(new_api_key)
+            .returning(API_KEY_COLUMNS)
+            .get_result::<ApiKey>(self.connection);
+
+        self.parse_data_access_result(result)
+            .map(|api_key| IssuedApiKey { api_key, key })
+    }
+
+    fn find(&self, id: i32) -> Result<ApiKey, DataAccessError> {
+        dsl::api_keys
+            .find(id)
+            .select(API_KEY_COLUMNS)
+            .first::<ApiKey>(self.connection)
+            .optional()?
+            .ok_or_else(|| DataAccessError::NotFound("Api key not found!".to_string()))
+    }
+}
+
+impl<'a> UseCase for ApiKeyDriver<'a> {}
+
+impl<'a> IssueApiKeyUseCase for ApiKeyDriver<'a> {
+    fn issue(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<IssuedApiKey, DataAccessError> {
+        let scopes = scopes.iter().map(|scope| scope.to_string()).collect();
+        self.insert(name, scopes, expires_at)
+    }
+}
+
+impl<'a> ListApiKeysUseCase for ApiKeyDriver<'a> {
+    fn list(&self) -> Result<Vec<ApiKey>, DataAccessError> {
+        let result = dsl::api_keys
+            .select(API_KEY_COLUMNS)
+            .order(dsl::id)
+            .load::<ApiKey>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
+impl<'a> RevokeApiKeyUseCase for ApiKeyDriver<'a> {
+    fn revoke(&self, id: i32) -> Result<ApiKey, DataAccessError> {
+        let api_key = self.find(id)?;
+        if api_key.revoked_at.is_some() {
+            return Ok(api_key);
+        }
+
+        let result = diesel::update(dsl::api_keys.find(id))
+            .set(dsl::revoked_at.eq(Utc::now()))
+            .returning(API_KEY_COLUMNS)
+            .get_result::<ApiKey>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
+impl<'a> RotateApiKeyUseCase for ApiKeyDriver<'a> {
+    fn rotate(
+        &self,
+        id: i32,
+        old_key_expires_at: DateTime<Utc>,
+    ) -> Result<IssuedApiKey, DataAccessError> {
+        self.connection.transaction(|| {
+            let api_key = self.find(id)?;
+            if !api_key.is_active(Utc::now()) {
+                return Err(DataAccessError::Conflict(
+                    "The api key is already expired or revoked!".to_string(),
+                ));
+            }
+
+            // 既に期限が近い場合は延長しない
+            let expires_at = api_key.expires_at.map_or(old_key_expires_at, |expires_at| {
+                expires_at.min(old_key_expires_at)
+            });
+            diesel::update(dsl::api_keys.find(id))
+                .set(dsl::expires_at.eq(expires_at))
+                .execute(self.connection)?;
+
+            self.insert(api_key.name, api_key.scopes, api_key.expires_at)
+        })
+    }
+}
+
+impl<'a> AuthenticateUseCase for ApiKeyDriver<'a> {
+    fn find_by_hash(&self, key_hash: String) -> Result<Option<ApiKey>, DataAccessError> {
+        let result = dsl::api_keys
+            .filter(dsl::key_hash.eq(key_hash))
+            .select(API_KEY_COLUMNS)
+            .first::<ApiKey>(self.connection)
+            .optional();
+
+        self.parse_data_access_result(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util;
+    use crate::usecase::api_keys::{authenticate, rotate};
+    use chrono::Duration;
+
+    #[test]
+    fn api_key_driver_scenario() {
+        let connection = test_util::connection_init();
+        let driver = ApiKeyDriver::new(&connection);
+
+        let issued = driver
+            .issue(
+                "payroll batch".to_string(),
+                vec![Scope::RecordsRead, Scope::RecordsWrite],
+                None,
+            )
+            .unwrap();
+        assert_eq!(issued.api_key.prefix, display_prefix(&issued.key));
+        assert_eq!(issued.api_key.scopes, vec!["records:read", "records:write"]);
+
+        let now = Utc::now();
+        let api_key =
+            authenticate::execute(ApiKeyDriver::new(&connection), &issued.key, now).unwrap();
+        assert_eq!(api_key.id, issued.api_key.id);
+        assert!(authenticate::execute(ApiKeyDriver::new(&connection), "atk_unknown", now).is_err());
+
+        // 古い鍵は猶予期間の間だけ使用できる
+        let rotated = rotate::execute(
+            ApiKeyDriver::new(&connection),
+            rotate::InputData {
+                id: issued.api_key.id,
+                grace_seconds: Some(60),
+            },
+        )
+        .unwrap();
+        assert_ne!(rotated.key, issued.key);
+        assert_eq!(rotated.api_key.name, "payroll batch");
+        assert_eq!(rotated.api_key.scopes, issued.api_key.scopes);
+        assert!(authenticate::execute(ApiKeyDriver::new(&connection), &issued.key, now).is_ok());
+        assert!(authenticate::execute(
+            ApiKeyDriver::new(&connection),
+            &issued.key,
+            now + Duration::seconds(120)
+        )
+        .is_err());
+
+        let revoked = driver.revoke(rotated.api_key.id).unwrap();
+        assert!(revoked.revoked_at.is_some());
+        assert!(authenticate::execute(ApiKeyDriver::new(&connection), &rotated.key, now).is_err());
+        // 失効済みの鍵はローテーションできない
+        assert!(matches!(
+            driver.rotate(rotated.api_key.id, now),
+            Err(DataAccessError::Conflict(_))
+        ));
+        assert!(matches!(
+            driver.revoke(0),
+            Err(DataAccessError::NotFound(_))
+        ));
+
+        assert_eq!(driver.list().unwrap().len(), 2);
+    }
+}
@@ -1,12 +1,25 @@
 use crate::database_utils::error::{DataAccessError, UseCase};
-use crate::domain::entity::attendance_record::AttendanceRecord;
-use crate::driver::common::get_registered_user;
-use crate::schema::attendance_records;
-use crate::usecase::attendance_records::{add, delete, search_by_user, update};
-use chrono::naive::serde::ts_seconds::{deserialize, serialize};
-use chrono::NaiveDateTime;
-use diesel::pg::PgConnection;
+use crate::domain::entity::attendance_record::{validate_breaks, AttendanceRecord};
+use crate::domain::entity::break_period::BreakPeriod;
+use crate::domain::entity::record_page::{Cursor, RecordPage};
+use crate::domain::entity::user::User;
+use crate::domain::entity::work_summary::{WorkSummary, WorkSummaryItem};
+use crate::domain::entity::working_time::{
+    classify, local_date, local_instant, LaborRules, WorkingTime,
+};
+use crate::driver::common::{get_active_user, get_registered_user};
+use crate::schema::{attendance_records, breaks, users};
+use crate::usecase::attendance_records::add::{AddRecordUseCase, BreakInput};
+use crate::usecase::attendance_records::search_by_user::{SortKey, SortOrder};
+use crate::usecase::attendance_records::{
+    add, break_end, break_start, clock_in, clock_out, delete, export, import, search_by_user,
+    summary, update,
+};
+use chrono::serde::{ts_seconds, ts_seconds_option};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use diesel::pg::{Pg, PgConnection};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text, Timestamptz};
 use serde::{Deserialize, Serialize};
 
 pub struct AttendanceRecordDriver<'a> {
@@ -19,150 +32,824 @@ impl<'a> AttendanceRecordDriver<'a> {
     }
 }
 
+impl<'a> AttendanceRecordDriver<'a> {
+    fn find_open_record(&self, user_id: i32) -> Result<Option<RecordItem>, DataAccessError> {
+        attendance_records::dsl::attendance_records
+            .filter(attendance_records::dsl::user_id.eq(user_id))
+            .filter(attendance_records::dsl::end_time.is_null())
+            .first::<RecordItem>(self.connection)
+            .optional()
+            .map_err(DataAccessError::from)
+    }
+
+    /// 勤務時間が重なる同じユーザーのレコード. 終了時刻がない場合は無限大とみなす.
+    fn find_overlapping_record(
+        &self,
+        user_id: i32,
+        start_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        exclude_id: Option<i32>,
+    ) -> Result<Option<i32>, DataAccessError> {
+        use attendance_records::dsl;
+
+        let mut query = dsl::attendance_records
+            .select(dsl::id)
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::end_time.is_null().or(dsl::end_time.gt(start_time)))
+            .into_boxed();
+        if let Some(end_time) = end_time {
+            query = query.filter(dsl::start_time.lt(end_time));
+        }
+        if let Some(exclude_id) = exclude_id {
+            query = query.filter(dsl::id.ne(exclude_id));
+        }
+
+        query
+            .order(dsl::start_time.asc())
+            .first::<i32>(self.connection)
+            .optional()
+            .map_err(DataAccessError::from)
+    }
+
+    fn check_overlap(
+        &self,
+        user_id: i32,
+        start_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        exclude_id: Option<i32>,
+    ) -> Result<(), DataAccessError> {
+        match self.find_overlapping_record(user_id, start_time, end_time, exclude_id)? {
+            Some(id) => Err(overlap_error(Some(id))),
+            None => Ok(()),
+        }
+    }
+
+    fn find_open_break(&self, record_id: i32) -> Result<Option<BreakItem>, DataAccessError> {
+        breaks::dsl::breaks
+            .filter(breaks::dsl::record_id.eq(record_id))
+            .filter(breaks::dsl::end_time.is_null())
+            .first::<BreakItem>(self.connection)
+            .optional()
+            .map_err(DataAccessError::from)
+    }
+
+    fn find_record(&self, id: i32) -> Result<AttendanceRecord, DataAccessError> {
+        let record = attendance_records::dsl::attendance_records
+            .find(id)
+            .first::<RecordItem>(self.connection)
+            .map_err(DataAccessError::from)?;
+
+        let mut records = self.to_entities(vec![record])?;
+        Ok(records.remove(0))
+    }
+
+    /// レコードに紐づく休憩を読み込み、entityに変換する.
+    fn to_entities(
+        &self,
+        records: Vec<RecordItem>,
+    ) -> Result<Vec<AttendanceRecord>, DataAccessError> {
+        let break_items = BreakItem::belonging_to(&records)
+            .order(breaks::dsl::start_time.asc())
+            .load::<BreakItem>(self.connection)
+            .map_err(DataAccessError::from)?
+            .grouped_by(&records);
+
+        let results = records
+            .into_iter()
+            .zip(break_items)
+            .map(|(record, break_items)| record.to_entity(break_items))
+            .collect();
+
+        Ok(results)
+    }
+
+    /// 出勤時刻が期間内の退勤済みレコード.
+    pub(crate) fn load_closed_records(
+        &self,
+        user_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<AttendanceRecord>, DataAccessError> {
+        let records = attendance_records::dsl::attendance_records
+            .filter(attendance_records::dsl::user_id.eq(user_id))
+            .filter(attendance_records::dsl::end_time.is_not_null())
+            .filter(attendance_records::dsl::start_time.ge(from))
+            .filter(attendance_records::dsl::start_time.lt(to))
+            .order(attendance_records::dsl::start_time.asc())
+            .load::<RecordItem>(self.connection)
+            .map_err(DataAccessError::from)?;
+
+        self.to_entities(records)
+    }
+
+    /// 労働時間の区分を設定する.
+    /// 週40時間を判定するため、週の起算日からのレコードを合わせて区分する.
+    pub(crate) fn set_working_time(
+        &self,
+        user: &User,
+        records: &mut [AttendanceRecord],
+        rules: &LaborRules,
+    ) -> Result<(), DataAccessError> {
+        let first = records.iter().map(|record| record.start_time).min();
+        let last = records.iter().map(|record| record.start_time).max();
+        let (first, last) = match (first, last) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+
+        let tz = user.tz();
+        let context = self.load_closed_records(
+            user.id,
+            week_start(rules, &tz, first),
+            last + Duration::seconds(1),
+        )?;
+        let results = classify(&context, rules, &tz);
+        for record in records.iter_mut() {
+            record.working_time = results.get(&record.id).cloned();
+        }
+
+        Ok(())
+    }
+
+    fn insert_breaks(&self, record_id: i32, inputs: &[BreakInput]) -> Result<(), DataAccessError> {
+        let new_breaks: Vec<NewBreak> = inputs
+            .iter()
+            .map(|input| NewBreak {
+                record_id,
+                start_time: Utc.timestamp(input.start_time, 0),
+                end_time: Some(Utc.timestamp(input.end_time, 0)),
+            })
+            .collect();
+
+        diesel::insert_into(breaks::table)
+            .values(new_breaks)
+            .execute(self.connection)
+            .map(|_| ())
+            .map_err(DataAccessError::from)
+    }
+}
+
 impl<'a> UseCase for AttendanceRecordDriver<'a> {}
 
+/// 日時を含む週の起算日の0:00.
+pub(crate) fn week_start<Tz: TimeZone>(
+    rules: &LaborRules,
+    tz: &Tz,
+    datetime: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let week = rules.week_of(local_date(tz, datetime));
+    local_instant(tz, week.and_hms(0, 0, 0))
+}
+
+fn invalid_breaks(message: String) -> DataAccessError {
+    DataAccessError::validation("breaks", &message)
+}
+
+const OVERLAP_CONSTRAINT: &str = "attendance_records_no_overlap";
+
+fn overlap_error(id: Option<i32>) -> DataAccessError {
+    match id {
+        Some(id) => DataAccessError::Conflict(format!("Record overlaps with record {}!", id)),
+        None => DataAccessError::Conflict("Record overlaps with another record!".to_string()),
+    }
+}
+
+/// 検証後に並行して登録されたレコードとの重複は、排他制約の違反として検出する.
+fn map_write_error(error: diesel::result::Error) -> DataAccessError {
+    match &error {
+        diesel::result::Error::DatabaseError(_, info)
+            if info.constraint_name() == Some(OVERLAP_CONSTRAINT) =>
+        {
+            overlap_error(None)
+        }
+        _ => DataAccessError::from(error),
+    }
+}
+
+/// 打刻時刻として扱う現在時刻(秒精度).
+fn now() -> DateTime<Utc> {
+    Utc.timestamp(Utc::now().timestamp(), 0)
+}
+
+fn break_ranges(inputs: &[BreakInput]) -> Vec<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    inputs
+        .iter()
+        .map(|input| {
+            (
+                Utc.timestamp(input.start_time, 0),
+                Some(Utc.timestamp(input.end_time, 0)),
+            )
+        })
+        .collect()
+}
+
 #[derive(Insertable)]
 #[table_name = "attendance_records"]
 struct NewRecord {
     user_id: i32,
-    start_time: NaiveDateTime,
-    end_time: NaiveDateTime,
-    break_time: i32,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    note: Option<String>,
 }
 
 #[derive(AsChangeset)]
 #[table_name = "attendance_records"]
 pub struct UpdateRecord {
-    start_time: Option<NaiveDateTime>,
-    end_time: Option<NaiveDateTime>,
-    break_time: Option<i32>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    note: Option<String>,
 }
 
 impl UpdateRecord {
-    fn new(start: Option<i64>, end: Option<i64>, break_time: Option<i32>) -> UpdateRecord {
-        let start_time = start.map(|timestamp| NaiveDateTime::from_timestamp(timestamp, 0));
-        let end_time = end.map(|timestamp| NaiveDateTime::from_timestamp(timestamp, 0));
+    fn new(start: Option<i64>, end: Option<i64>, note: Option<String>) -> UpdateRecord {
+        let start_time = start.map(|timestamp| Utc.timestamp(timestamp, 0));
+        let end_time = end.map(|timestamp| Utc.timestamp(timestamp, 0));
 
         UpdateRecord {
             start_time,
             end_time,
-            break_time,
+            note,
         }
     }
 }
 
-#[derive(Debug, Queryable, Serialize, Deserialize)]
+#[derive(Debug, Queryable, Identifiable, Serialize, Deserialize)]
+#[table_name = "attendance_records"]
 struct RecordItem {
     id: i32,
     user_id: i32,
-    #[serde(serialize_with = "serialize")]
-    #[serde(deserialize_with = "deserialize")]
-    start_time: NaiveDateTime,
-    #[serde(serialize_with = "serialize")]
-    #[serde(deserialize_with = "deserialize")]
-    end_time: NaiveDateTime,
-    break_time: i32,
+    #[serde(with = "ts_seconds")]
+    start_time: DateTime<Utc>,
+    #[serde(with = "ts_seconds_option")]
+    end_time: Option<DateTime<Utc>>,
+    note: Option<String>,
 }
 
 impl RecordItem {
-    fn to_entity(&self) -> AttendanceRecord {
+    fn to_entity(&self, break_items: Vec<BreakItem>) -> AttendanceRecord {
         AttendanceRecord {
+            note: self.note.clone(),
+            ..AttendanceRecord::new(
+                self.id,
+                self.user_id,
+                self.start_time,
+                self.end_time,
+                break_items.iter().map(|item| item.to_entity()).collect(),
+            )
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "breaks"]
+struct NewBreak {
+    record_id: i32,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Queryable, Identifiable, Associations)]
+#[belongs_to(RecordItem, foreign_key = "record_id")]
+#[table_name = "breaks"]
+struct BreakItem {
+    id: i32,
+    record_id: i32,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+impl BreakItem {
+    fn to_entity(&self) -> BreakPeriod {
+        BreakPeriod {
             id: self.id,
-            user_id: self.user_id,
+            record_id: self.record_id,
             start_time: self.start_time,
             end_time: self.end_time,
-            break_time: self.break_time,
         }
     }
 }
 
+#[derive(Debug, QueryableByName)]
+struct SummaryRow {
+    #[sql_type = "Timestamptz"]
+    bucket: DateTime<Utc>,
+    #[sql_type = "BigInt"]
+    record_count: i64,
+    #[sql_type = "BigInt"]
+    span_seconds: i64,
+    #[sql_type = "BigInt"]
+    break_seconds: i64,
+}
+
+impl SummaryRow {
+    fn to_entity(&self) -> WorkSummaryItem {
+        WorkSummaryItem {
+            start: self.bucket,
+            record_count: self.record_count,
+            worked_minutes: (self.span_seconds - self.break_seconds) / 60,
+            break_minutes: self.break_seconds / 60,
+            working_time: WorkingTime::default(),
+        }
+    }
+}
+
+/// ユーザーのtime zoneにおける出勤時刻を`date_trunc`した単位毎に、
+/// 退勤済みレコードの拘束時間と休憩時間を集計する.
+const SUMMARY_QUERY: &str = "
+    SELECT
+        date_trunc($1, records.start_time AT TIME ZONE $5) AT TIME ZONE $5 AS bucket,
+        COUNT(*) AS record_count,
+        SUM(EXTRACT(EPOCH FROM (records.end_time - records.start_time)))::BIGINT AS span_seconds,
+        COALESCE(SUM(record_breaks.break_seconds), 0)::BIGINT AS break_seconds
+    FROM attendance_records AS records
+    LEFT JOIN (
+        SELECT record_id, SUM(EXTRACT(EPOCH FROM (end_time - start_time))) AS break_seconds
+        FROM breaks
+        WHERE end_time IS NOT NULL
+        GROUP BY record_id
+    ) AS record_breaks ON record_breaks.record_id = records.id
+    WHERE records.user_id = $2
+        AND records.end_time IS NOT NULL
+        AND records.start_time >= $3
+        AND records.start_time < $4
+    GROUP BY bucket
+    ORDER BY bucket
+";
+
 impl<'a> add::AddRecordUseCase for AttendanceRecordDriver<'a> {
     fn add_record(&self, input: add::InputData) -> Result<AttendanceRecord, DataAccessError> {
-        let user = get_registered_user(self.connection, input.uid)
-            .or_else(|_| Err(DataAccessError::InternalError))?;
+        let user = get_active_user(self.connection, input.uid.clone())?;
         let new_record = NewRecord {
             user_id: user.id,
-            start_time: NaiveDateTime::from_timestamp(input.start_time, 0),
-            end_time: NaiveDateTime::from_timestamp(input.end_time, 0),
-            break_time: input.break_time,
+            start_time: Utc.timestamp(input.start_time, 0),
+            end_time: Some(Utc.timestamp(input.end_time, 0)),
+            note: input.note.clone(),
         };
+        validate_breaks(
+            new_record.start_time,
+            new_record.end_time,
+            &break_ranges(&input.breaks),
+        )
+        .map_err(invalid_breaks)?;
+
+        self.connection.transaction(|| {
+            self.check_overlap(user.id, new_record.start_time, new_record.end_time, None)?;
+            let record_result = diesel::insert_into(attendance_records::table)
+                .values(new_record)
+                .get_result::<RecordItem>(self.connection)
+                .map_err(map_write_error)?;
+            self.insert_breaks(record_result.id, &input.breaks)?;
+
+            self.find_record(record_result.id)
+        })
+    }
+}
+
+/// 一括登録のtransactionを終了する理由.
+enum ImportAbort {
+    /// 失敗した行があるため取り消す
+    Rollback(import::ImportReport),
+    Error(DataAccessError),
+}
+
+impl From<diesel::result::Error> for ImportAbort {
+    fn from(error: diesel::result::Error) -> Self {
+        ImportAbort::Error(DataAccessError::from(error))
+    }
+}
+
+impl<'a> import::ImportRecordsUseCase for AttendanceRecordDriver<'a> {
+    fn import_records(
+        &self,
+        rows: Vec<import::ImportRow>,
+        mode: import::Mode,
+    ) -> Result<import::ImportReport, DataAccessError> {
+        let result = self.connection.transaction(|| {
+            let mut results = Vec::with_capacity(rows.len());
+            for row in rows {
+                let uid = row.input.as_ref().ok().map(|input| input.uid.clone());
+                // 行毎のsavepointで登録するため、失敗した行は他の行に影響しない
+                let added = match row.input {
+                    Ok(input) => match self.add_record(input) {
+                        Ok(record) => Ok(record.id),
+                        Err(e @ DataAccessError::Internal(_))
+                        | Err(e @ DataAccessError::Unavailable(_)) => {
+                            return Err(ImportAbort::Error(e))
+                        }
+                        Err(e) => Err(import::row_errors(e)),
+                    },
+                    Err(errors) => Err(errors),
+                };
+                results.push(match added {
+                    Ok(record_id) => import::RowResult {
+                        line: row.line,
+                        uid,
+                        record_id: Some(record_id),
+                        errors: vec![],
+                    },
+                    Err(errors) => import::RowResult {
+                        line: row.line,
+                        uid,
+                        record_id: None,
+                        errors,
+                    },
+                });
+            }
+
+            let failed = results.iter().filter(|row| !row.errors.is_empty()).count();
+            let report = import::ImportReport {
+                imported: results.len() - failed,
+                failed,
+                committed: true,
+                rows: results,
+            };
+            if failed > 0 && mode == import::Mode::AllOrNothing {
+                return Err(ImportAbort::Rollback(report));
+            }
+            Ok(report)
+        });
+
+        match result {
+            Ok(report) => Ok(report),
+            Err(ImportAbort::Rollback(mut report)) => {
+                for row in report.rows.iter_mut() {
+                    row.record_id = None;
+                }
+                report.imported = 0;
+                report.committed = false;
+                Ok(report)
+            }
+            Err(ImportAbort::Error(e)) => Err(e),
+        }
+    }
+}
+
+impl<'a> clock_in::ClockInUseCase for AttendanceRecordDriver<'a> {
+    fn clock_in(&self, input: clock_in::InputData) -> Result<AttendanceRecord, DataAccessError> {
+        let user = get_active_user(self.connection, input.uid)?;
+        if self.find_open_record(user.id)?.is_some() {
+            return Err(DataAccessError::Conflict("Already clocked in!".to_string()));
+        }
+
+        let new_record = NewRecord {
+            user_id: user.id,
+            start_time: now(),
+            end_time: None,
+            note: None,
+        };
+        self.check_overlap(user.id, new_record.start_time, None, None)?;
 
         let record_result = diesel::insert_into(attendance_records::table)
             .values(new_record)
             .get_result::<RecordItem>(self.connection)
-            .or_else(|_| Err(DataAccessError::InternalError))?;
+            .map_err(map_write_error)?;
+
+        Ok(record_result.to_entity(vec![]))
+    }
+}
+
+impl<'a> clock_out::ClockOutUseCase for AttendanceRecordDriver<'a> {
+    fn clock_out(&self, input: clock_out::InputData) -> Result<AttendanceRecord, DataAccessError> {
+        let user = get_active_user(self.connection, input.uid)?;
+        let open_record = self
+            .find_open_record(user.id)?
+            .ok_or_else(|| DataAccessError::Conflict("Not clocked in!".to_string()))?;
+        if self.find_open_break(open_record.id)?.is_some() {
+            return Err(DataAccessError::Conflict(
+                "Break is not finished!".to_string(),
+            ));
+        }
+
+        diesel::update(attendance_records::dsl::attendance_records.find(open_record.id))
+            .set(attendance_records::dsl::end_time.eq(now()))
+            .execute(self.connection)
+            .map_err(DataAccessError::from)?;
+
+        self.find_record(open_record.id)
+    }
+}
+
+impl<'a> break_start::BreakStartUseCase for AttendanceRecordDriver<'a> {
+    fn break_start(
+        &self,
+        input: break_start::InputData,
+    ) -> Result<AttendanceRecord, DataAccessError> {
+        let user = get_active_user(self.connection, input.uid)?;
+        let open_record = self
+            .find_open_record(user.id)?
+            .ok_or_else(|| DataAccessError::Conflict("Not clocked in!".to_string()))?;
+        if self.find_open_break(open_record.id)?.is_some() {
+            return Err(DataAccessError::Conflict("Already on a break!".to_string()));
+        }
+
+        diesel::insert_into(breaks::table)
+            .values(NewBreak {
+                record_id: open_record.id,
+                start_time: now(),
+                end_time: None,
+            })
+            .execute(self.connection)
+            .map_err(DataAccessError::from)?;
+
+        self.find_record(open_record.id)
+    }
+}
+
+impl<'a> break_end::BreakEndUseCase for AttendanceRecordDriver<'a> {
+    fn break_end(&self, input: break_end::InputData) -> Result<AttendanceRecord, DataAccessError> {
+        let user = get_active_user(self.connection, input.uid)?;
+        let open_record = self
+            .find_open_record(user.id)?
+            .ok_or_else(|| DataAccessError::Conflict("Not clocked in!".to_string()))?;
+        let open_break = self
+            .find_open_break(open_record.id)?
+            .ok_or_else(|| DataAccessError::Conflict("Not on a break!".to_string()))?;
 
-        Ok(record_result.to_entity())
+        diesel::update(breaks::dsl::breaks.find(open_break.id))
+            .set(breaks::dsl::end_time.eq(now()))
+            .execute(self.connection)
+            .map_err(DataAccessError::from)?;
+
+        self.find_record(open_record.id)
     }
 }
 
+/// 検索条件(並び順・ページ位置を除く)で絞り込んだクエリ.
+fn search_query<'a>(
+    user_id: i32,
+    input: &search_by_user::InputData,
+) -> attendance_records::BoxedQuery<'a, Pg> {
+    use attendance_records::dsl;
+
+    let mut query = dsl::attendance_records
+        .filter(dsl::user_id.eq(user_id))
+        .into_boxed();
+    if let Some(from) = input.from {
+        query = query.filter(dsl::start_time.ge(Utc.timestamp(from, 0)));
+    }
+    if let Some(to) = input.to {
+        query = query.filter(dsl::start_time.lt(Utc.timestamp(to, 0)));
+    }
+    if input.open_only {
+        query = query.filter(dsl::end_time.is_null());
+    }
+    if input.has_note {
+        query = query.filter(dsl::note.is_not_null().and(dsl::note.ne("")));
+    }
+
+    query
+}
+
 impl<'a> search_by_user::SearchRecordsByUserUseCase for AttendanceRecordDriver<'a> {
-    fn get_records(
+    fn get_records(&self, input: search_by_user::InputData) -> Result<RecordPage, DataAccessError> {
+        use attendance_records::dsl;
+
+        let user = get_registered_user(self.connection, input.uid.clone())?;
+        let cursor = match &input.cursor {
+            Some(cursor) => Some(
+                Cursor::decode(cursor)
+                    .map_err(|message| DataAccessError::validation("cursor", &message))?,
+            ),
+            None => None,
+        };
+
+        let total = if input.with_total {
+            let total = search_query(user.id, &input)
+                .count()
+                .get_result::<i64>(self.connection)
+                .map_err(DataAccessError::from)?;
+            Some(total)
+        } else {
+            None
+        };
+
+        let mut query = search_query(user.id, &input);
+        // 同じ時刻のレコードの順序を固定するため、idを第2キーとする
+        let sort = input.sort_key();
+        let order = input.order.unwrap_or(SortOrder::Desc);
+        query = match (sort, order) {
+            (SortKey::StartTime, SortOrder::Asc) => {
+                query.order((dsl::start_time.asc(), dsl::id.asc()))
+            }
+            (SortKey::StartTime, SortOrder::Desc) => {
+                query.order((dsl::start_time.desc(), dsl::id.desc()))
+            }
+            (SortKey::EndTime, SortOrder::Asc) => query.order((dsl::end_time.asc(), dsl::id.asc())),
+            (SortKey::EndTime, SortOrder::Desc) => {
+                query.order((dsl::end_time.desc(), dsl::id.desc()))
+            }
+            (SortKey::Id, SortOrder::Asc) => query.order(dsl::id.asc()),
+            (SortKey::Id, SortOrder::Desc) => query.order(dsl::id.desc()),
+        };
+        query = match (cursor, order) {
+            (Some(cursor), SortOrder::Asc) => query.filter(
+                dsl::start_time.gt(cursor.start_time).or(dsl::start_time
+                    .eq(cursor.start_time)
+                    .and(dsl::id.gt(cursor.id))),
+            ),
+            (Some(cursor), SortOrder::Desc) => query.filter(
+                dsl::start_time.lt(cursor.start_time).or(dsl::start_time
+                    .eq(cursor.start_time)
+                    .and(dsl::id.lt(cursor.id))),
+            ),
+            (None, _) => query.offset(i64::from(input.count) * (i64::from(input.page) - 1)),
+        };
+
+        // 次のページの有無を判定するため、1件多く取得する
+        let mut record_results: Vec<RecordItem> = query
+            .limit(input.count as i64 + 1)
+            .load::<RecordItem>(self.connection)
+            .map_err(DataAccessError::from)?;
+        let has_more = record_results.len() > input.count as usize;
+        record_results.truncate(input.count as usize);
+
+        let mut records = self.to_entities(record_results)?;
+        self.set_working_time(&user, &mut records, &LaborRules::default())?;
+        let next_cursor = match records.last() {
+            Some(last) if has_more && sort == SortKey::StartTime => Some(Cursor::of(last).encode()),
+            _ => None,
+        };
+
+        Ok(RecordPage {
+            records,
+            next_cursor,
+            has_more,
+            total,
+        })
+    }
+}
+
+impl<'a> export::ExportRecordsUseCase for AttendanceRecordDriver<'a> {
+    fn export_batch(
         &self,
-        input: search_by_user::InputData,
-    ) -> Result<Vec<AttendanceRecord>, DataAccessError> {
-        let offset = input.count * (input.page - 1);
-        let user_id = get_registered_user(&self.connection, input.uid.clone())?;
-
-        let record_results: Vec<RecordItem> = attendance_records::dsl::attendance_records
-            .filter(attendance_records::dsl::user_id.eq(user_id.id))
-            .limit(input.count as i64)
-            .offset(offset as i64)
-            .order(attendance_records::dsl::id.desc())
+        input: &export::InputData,
+        after: Option<export::ExportCursor>,
+        limit: i64,
+    ) -> Result<Vec<export::ExportRow>, DataAccessError> {
+        use attendance_records::dsl;
+
+        let mut query = dsl::attendance_records
+            .filter(dsl::start_time.ge(Utc.timestamp(input.from, 0)))
+            .filter(dsl::start_time.lt(Utc.timestamp(input.to, 0)))
+            .into_boxed();
+        if let Some(uid) = &input.uid {
+            let user = get_registered_user(self.connection, uid.clone())?;
+            query = query.filter(dsl::user_id.eq(user.id));
+        }
+        if let Some(after) = after {
+            query = query.filter(
+                dsl::user_id
+                    .gt(after.user_id)
+                    .or(dsl::user_id.eq(after.user_id).and(
+                        dsl::start_time.gt(after.start_time).or(dsl::start_time
+                            .eq(after.start_time)
+                            .and(dsl::id.gt(after.id))),
+                    )),
+            );
+        }
+        let record_results = query
+            .order((dsl::user_id.asc(), dsl::start_time.asc(), dsl::id.asc()))
+            .limit(limit)
             .load::<RecordItem>(self.connection)
-            .or_else(|_| Err(DataAccessError::InternalError))?;
+            .map_err(DataAccessError::from)?;
 
-        let results = record_results
+        let user_ids = record_results
             .iter()
-            .map(|result| result.to_entity())
-            .collect();
+            .map(|record| record.user_id)
+            .collect::<Vec<i32>>();
+        let record_users = users::dsl::users
+            .filter(users::dsl::id.eq_any(user_ids))
+            .order(users::dsl::id.asc())
+            .load::<User>(self.connection)
+            .map_err(DataAccessError::from)?;
 
-        Ok(results)
+        // レコードはユーザー順に並んでいるため、先頭から順にユーザー毎に区切る
+        let mut records = self.to_entities(record_results)?.into_iter().peekable();
+        let mut rows = Vec::new();
+        for user in &record_users {
+            let mut user_records = Vec::new();
+            while let Some(record) = records.next_if(|record| record.user_id == user.id) {
+                user_records.push(record);
+            }
+            self.set_working_time(user, &mut user_records, &LaborRules::default())?;
+            rows.extend(user_records.into_iter().map(|record| export::ExportRow {
+                uid: user.uid.clone(),
+                time_zone: user.tz(),
+                record,
+            }));
+        }
+
+        Ok(rows)
+    }
+}
+
+impl<'a> summary::SummarizeRecordsUseCase for AttendanceRecordDriver<'a> {
+    fn summarize(&self, input: summary::InputData) -> Result<WorkSummary, DataAccessError> {
+        let user = get_registered_user(self.connection, input.uid)?;
+        let from = Utc.timestamp(input.from, 0);
+        let to = Utc.timestamp(input.to, 0);
+
+        let rows = diesel::sql_query(SUMMARY_QUERY)
+            .bind::<Text, _>(input.group_by.as_str())
+            .bind::<Integer, _>(user.id)
+            .bind::<Timestamptz, _>(from)
+            .bind::<Timestamptz, _>(to)
+            .bind::<Text, _>(&user.time_zone)
+            .load::<SummaryRow>(self.connection)
+            .map_err(DataAccessError::from)?;
+        let mut items: Vec<WorkSummaryItem> = rows.iter().map(|row| row.to_entity()).collect();
+
+        // 労働時間の区分は週の起算日からのレコードで判定し、期間内のレコードのみ集計する
+        let rules = LaborRules::default();
+        let tz = user.tz();
+        let records = self.load_closed_records(user.id, week_start(&rules, &tz, from), to)?;
+        let results = classify(&records, &rules, &tz);
+        for record in records.iter().filter(|record| record.start_time >= from) {
+            let bucket = input
+                .group_by
+                .truncate(record.start_time.with_timezone(&tz).naive_local());
+            let item = items
+                .iter_mut()
+                .find(|item| item.start.with_timezone(&tz).naive_local() == bucket);
+            if let (Some(item), Some(working_time)) = (item, results.get(&record.id)) {
+                item.working_time += *working_time;
+            }
+        }
+
+        Ok(WorkSummary::new(items))
     }
 }
 
 impl<'a> update::UpdateRecordUseCase for AttendanceRecordDriver<'a> {
     fn update_record(&self, input: update::InputData) -> Result<(), DataAccessError> {
-        let user = get_registered_user(self.connection, input.uid.clone())?;
-        let record = attendance_records::dsl::attendance_records
-            .find(input.id)
-            .first::<RecordItem>(self.connection)
-            .or_else(|_| Err(DataAccessError::InternalError))?;
+        let user = get_active_user(self.connection, input.uid.clone())?;
+        let record = self.find_record(input.id)?;
         if record.user_id != user.id {
-            return Err(DataAccessError::InternalError);
+            return Err(DataAccessError::Forbidden(
+                "The record belongs to another user!".to_string(),
+            ));
         }
 
-        let result = diesel::update(attendance_records::dsl::attendance_records.find(input.id))
-            .set(UpdateRecord::new(
-                input.start_time,
-                input.end_time,
-                input.break_time,
-            ))
-            .get_result::<RecordItem>(self.connection);
-
-        match result {
-            Ok(_) => Ok(()),
-            Err(_) => Err(DataAccessError::InternalError),
+        // 更新後の勤務時間に対して休憩を検証する
+        let update_record = UpdateRecord::new(input.start_time, input.end_time, input.note.clone());
+        let break_ranges = match &input.breaks {
+            Some(inputs) => break_ranges(inputs),
+            None => record
+                .breaks
+                .iter()
+                .map(|item| (item.start_time, item.end_time))
+                .collect(),
+        };
+        let start_time = update_record.start_time.unwrap_or(record.start_time);
+        let end_time = update_record.end_time.or(record.end_time);
+        match end_time {
+            Some(end_time) if end_time <= start_time => {
+                return Err(DataAccessError::validation(
+                    "end_time",
+                    "must be after start_time",
+                ));
+            }
+            _ => {}
         }
+        validate_breaks(start_time, end_time, &break_ranges).map_err(invalid_breaks)?;
+
+        self.connection.transaction(|| {
+            self.check_overlap(user.id, start_time, end_time, Some(record.id))?;
+            diesel::update(attendance_records::dsl::attendance_records.find(input.id))
+                .set(update_record)
+                .execute(self.connection)
+                .map_err(map_write_error)?;
+
+            if let Some(inputs) = &input.breaks {
+                diesel::delete(breaks::dsl::breaks.filter(breaks::dsl::record_id.eq(input.id)))
+                    .execute(self.connection)
+                    .map_err(DataAccessError::from)?;
+                self.insert_breaks(input.id, inputs)?;
+            }
+
+            Ok(())
+        })
     }
 }
 
 impl<'a> delete::DeleteRecordUseCase for AttendanceRecordDriver<'a> {
     fn delete_record(&self, input: delete::InputData) -> Result<(), DataAccessError> {
-        let user = get_registered_user(self.connection, input.uid.clone())?;
+        let user = get_active_user(self.connection, input.uid.clone())?;
         let record = attendance_records::dsl::attendance_records
             .find(input.id)
             .first::<RecordItem>(self.connection)
-            .or_else(|_| Err(DataAccessError::InternalError))?;
+            .map_err(DataAccessError::from)?;
         if record.user_id != user.id {
-            return Err(DataAccessError::InternalError);
+            return Err(DataAccessError::Forbidden(
+                "The record belongs to another user!".to_string(),
+            ));
         }
 
         let result = diesel::delete(attendance_records::dsl::attendance_records.find(input.id))
             .execute(self.connection);
         match result {
             Ok(_) => Ok(()),
-            Err(_) => Err(DataAccessError::InternalError),
+            Err(error) => Err(DataAccessError::from(error)),
         }
     }
 }
@@ -173,11 +860,21 @@ mod test {
     use super::*;
     use crate::database_utils::pool::test_util;
     use crate::driver::users::test_utils::test_user_by_connection;
+    use crate::driver::users::UserDriver;
     use crate::usecase::attendance_records::add::{self, AddRecordUseCase};
+    use crate::usecase::attendance_records::break_end::BreakEndUseCase;
+    use crate::usecase::attendance_records::break_start::BreakStartUseCase;
+    use crate::usecase::attendance_records::clock_in::ClockInUseCase;
+    use crate::usecase::attendance_records::clock_out::ClockOutUseCase;
     use crate::usecase::attendance_records::delete::DeleteRecordUseCase;
+    use crate::usecase::attendance_records::export::{Column, ExportCursor, ExportRecordsUseCase};
+    use crate::usecase::attendance_records::import::{ImportRecordsUseCase, Mode, StandardCsv};
     use crate::usecase::attendance_records::search_by_user::SearchRecordsByUserUseCase;
+    use crate::usecase::attendance_records::summary::{GroupBy, SummarizeRecordsUseCase};
     use crate::usecase::attendance_records::update::UpdateRecordUseCase;
-    use chrono::{Duration, Local};
+    use crate::usecase::users::add::CreateUserUseCase;
+    use chrono::{Datelike, Duration, Local};
+    use chrono_tz::Asia::Tokyo;
 
     /// # scenario
     ///
@@ -187,6 +884,7 @@ mod test {
     /// - get
     /// - not update
     /// - get
+    /// - invalid update
     /// - delete
     /// - get
     #[test]
@@ -195,23 +893,29 @@ mod test {
         let attendance_driver = AttendanceRecordDriver::new(&connection);
         let test_user = test_user_by_connection(&connection);
         let end_time = Local::now();
-        let end_time_naive = NaiveDateTime::from_timestamp(end_time.timestamp(), 0);
+        let end_time_utc = Utc.timestamp(end_time.timestamp(), 0);
         let start_time = end_time - Duration::hours(8);
-        let start_time_naive = NaiveDateTime::from_timestamp(start_time.timestamp(), 0);
-        let break_time = 60 * 60 * 1000;
+        let start_time_utc = Utc.timestamp(start_time.timestamp(), 0);
+        let break_start = start_time + Duration::hours(3);
+        let breaks = vec![BreakInput {
+            start_time: break_start.timestamp(),
+            end_time: (break_start + Duration::hours(1)).timestamp(),
+        }];
 
         let added_record = attendance_driver
             .add_record(add::InputData {
                 uid: test_user.uid.clone(),
                 start_time: start_time.timestamp(),
                 end_time: end_time.timestamp(),
-                break_time,
+                breaks: breaks.clone(),
+                note: None,
             })
             .unwrap();
         assert_eq!(added_record.user_id, test_user.id);
-        assert_eq!(added_record.start_time, start_time_naive);
-        assert_eq!(added_record.end_time, end_time_naive);
-        assert_eq!(added_record.break_time, break_time);
+        assert_eq!(added_record.start_time, start_time_utc);
+        assert_eq!(added_record.end_time, Some(end_time_utc));
+        assert_eq!(added_record.breaks.len(), 1);
+        assert_eq!(added_record.break_seconds, 60 * 60);
 
         let records_by_user = attendance_driver
             .get_records(search_by_user::InputData {
@@ -220,28 +924,41 @@ mod test {
                     .uid,
                 page: 1,
                 count: 10,
+                ..Default::default()
             })
-            .unwrap();
+            .unwrap()
+            .records;
         assert_eq!(records_by_user.len(), 1);
         let record_by_user = records_by_user.first().unwrap();
         assert_eq!(record_by_user.user_id, test_user.id);
-        assert_eq!(record_by_user.start_time, start_time_naive);
-        assert_eq!(record_by_user.end_time, end_time_naive);
-        assert_eq!(record_by_user.break_time, break_time);
+        assert_eq!(record_by_user.start_time, start_time_utc);
+        assert_eq!(record_by_user.end_time, Some(end_time_utc));
+        assert_eq!(record_by_user.break_seconds, 60 * 60);
 
         // update
         let end_time2 = Local::now() + Duration::hours(1);
-        let end_time2_naive = NaiveDateTime::from_timestamp(end_time2.timestamp(), 0);
+        let end_time2_utc = Utc.timestamp(end_time2.timestamp(), 0);
         let start_time2 = end_time2 - Duration::hours(8);
-        let start_time2_naive = NaiveDateTime::from_timestamp(start_time2.timestamp(), 0);
-        let break_time2 = 60 * 60 * 1000 * 2;
-        let _update_result = attendance_driver.update_record(update::InputData {
+        let start_time2_utc = Utc.timestamp(start_time2.timestamp(), 0);
+        let break_start2 = start_time2 + Duration::hours(3);
+        let update_result = attendance_driver.update_record(update::InputData {
             uid: test_user.uid.clone(),
             id: record_by_user.id,
             start_time: Some(start_time2.timestamp()),
             end_time: Some(end_time2.timestamp()),
-            break_time: Some(break_time2),
+            breaks: Some(vec![
+                BreakInput {
+                    start_time: break_start2.timestamp(),
+                    end_time: (break_start2 + Duration::hours(1)).timestamp(),
+                },
+                BreakInput {
+                    start_time: (break_start2 + Duration::hours(2)).timestamp(),
+                    end_time: (break_start2 + Duration::hours(3)).timestamp(),
+                },
+            ]),
+            note: Some("在宅勤務".to_string()),
         });
+        assert!(update_result.is_ok());
 
         let records_by_user = attendance_driver
             .get_records(search_by_user::InputData {
@@ -250,14 +967,17 @@ mod test {
                     .uid,
                 page: 1,
                 count: 10,
+                ..Default::default()
             })
-            .unwrap();
+            .unwrap()
+            .records;
         assert_eq!(records_by_user.len(), 1);
         let record_by_user = records_by_user.first().unwrap();
         assert_eq!(record_by_user.user_id, test_user.id);
-        assert_eq!(record_by_user.start_time, start_time2_naive);
-        assert_eq!(record_by_user.end_time, end_time2_naive);
-        assert_eq!(record_by_user.break_time, break_time2);
+        assert_eq!(record_by_user.start_time, start_time2_utc);
+        assert_eq!(record_by_user.end_time, Some(end_time2_utc));
+        assert_eq!(record_by_user.breaks.len(), 2);
+        assert_eq!(record_by_user.break_seconds, 60 * 60 * 2);
 
         // not update
         let _update_result = attendance_driver.update_record(update::InputData {
@@ -265,7 +985,8 @@ mod test {
             id: record_by_user.id,
             start_time: None,
             end_time: None,
-            break_time: None,
+            breaks: None,
+            note: None,
         });
 
         let records_by_user = attendance_driver
@@ -275,13 +996,26 @@ mod test {
                     .uid,
                 page: 1,
                 count: 10,
+                ..Default::default()
             })
-            .unwrap();
+            .unwrap()
+            .records;
         assert_eq!(records_by_user.len(), 1);
         let record_by_user = records_by_user.first().unwrap();
-        assert_eq!(record_by_user.start_time, start_time2_naive);
-        assert_eq!(record_by_user.end_time, end_time2_naive);
-        assert_eq!(record_by_user.break_time, break_time2);
+        assert_eq!(record_by_user.start_time, start_time2_utc);
+        assert_eq!(record_by_user.end_time, Some(end_time2_utc));
+        assert_eq!(record_by_user.break_seconds, 60 * 60 * 2);
+
+        // 休憩が勤務時間外になる更新
+        let update_result = attendance_driver.update_record(update::InputData {
+            uid: test_user.uid.clone(),
+            id: record_by_user.id,
+            start_time: None,
+            end_time: Some((start_time2 + Duration::hours(4)).timestamp()),
+            breaks: None,
+            note: None,
+        });
+        assert!(update_result.is_err());
 
         // delete
         let _result = attendance_driver.delete_record(delete::InputData {
@@ -295,8 +1029,517 @@ mod test {
                     .uid,
                 page: 1,
                 count: 10,
+                ..Default::default()
             })
-            .unwrap();
+            .unwrap()
+            .records;
         assert!(records_by_user.is_empty());
     }
+
+    /// # scenario
+    ///
+    /// - clock out(not clocked in)
+    /// - clock in
+    /// - clock in(already clocked in)
+    /// - break end(not on a break)
+    /// - break start
+    /// - break start(already on a break)
+    /// - clock out(on a break)
+    /// - break end
+    /// - clock out
+    /// - clock out(not clocked in)
+    #[test]
+    fn clock_in_out_scenario() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+
+        let clock_out_result = attendance_driver.clock_out(clock_out::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(clock_out_result.is_err());
+
+        let clocked_in = attendance_driver
+            .clock_in(clock_in::InputData {
+                uid: test_user.uid.clone(),
+            })
+            .unwrap();
+        assert_eq!(clocked_in.user_id, test_user.id);
+        assert!(clocked_in.is_open());
+
+        let clock_in_result = attendance_driver.clock_in(clock_in::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(clock_in_result.is_err());
+
+        let break_end_result = attendance_driver.break_end(break_end::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(break_end_result.is_err());
+
+        let on_break = attendance_driver
+            .break_start(break_start::InputData {
+                uid: test_user.uid.clone(),
+            })
+            .unwrap();
+        assert!(on_break.is_on_break());
+
+        let break_start_result = attendance_driver.break_start(break_start::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(break_start_result.is_err());
+
+        let clock_out_result = attendance_driver.clock_out(clock_out::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(clock_out_result.is_err());
+
+        let break_ended = attendance_driver
+            .break_end(break_end::InputData {
+                uid: test_user.uid.clone(),
+            })
+            .unwrap();
+        assert!(!break_ended.is_on_break());
+        assert_eq!(break_ended.breaks.len(), 1);
+
+        let clocked_out = attendance_driver
+            .clock_out(clock_out::InputData {
+                uid: test_user.uid.clone(),
+            })
+            .unwrap();
+        assert_eq!(clocked_out.id, clocked_in.id);
+        assert!(!clocked_out.is_open());
+        assert!(clocked_out.end_time.unwrap() >= clocked_out.start_time);
+        assert_eq!(clocked_out.breaks.len(), 1);
+
+        let clock_out_result = attendance_driver.clock_out(clock_out::InputData {
+            uid: test_user.uid.clone(),
+        });
+        assert!(clock_out_result.is_err());
+    }
+
+    #[test]
+    fn search_records_test() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let date = |day: u32, hour: u32| Tokyo.ymd(2020, 10, day).and_hms(hour, 0, 0).timestamp();
+
+        // 10/2(備考あり), 10/1, 10/3の順に登録した退勤済みレコードと、出勤中のレコード
+        for (day, note) in [(2, Some("直行直帰")), (1, None), (3, Some(""))] {
+            attendance_driver
+                .add_record(add::InputData {
+                    uid: test_user.uid.clone(),
+                    start_time: date(day, 9),
+                    end_time: date(day, 18 - day),
+                    breaks: vec![],
+                    note: note.map(|note| note.to_string()),
+                })
+                .unwrap();
+        }
+        let open_record = attendance_driver
+            .clock_in(clock_in::InputData {
+                uid: test_user.uid.clone(),
+            })
+            .unwrap();
+        let search = |input: search_by_user::InputData| -> Vec<AttendanceRecord> {
+            attendance_driver
+                .get_records(search_by_user::InputData {
+                    uid: test_user.uid.clone(),
+                    page: 1,
+                    count: 10,
+                    ..input
+                })
+                .unwrap()
+                .records
+        };
+        let start_days = |records: &[AttendanceRecord]| -> Vec<u32> {
+            records
+                .iter()
+                .map(|record| record.start_time.with_timezone(&Tokyo).day())
+                .collect()
+        };
+
+        // 既定はidの降順
+        let records = search(Default::default());
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].id, open_record.id);
+        assert_eq!(start_days(&records[1..]), vec![3, 1, 2]);
+
+        let records = search(search_by_user::InputData {
+            from: Some(date(2, 0)),
+            to: Some(date(4, 0)),
+            sort: Some(SortKey::StartTime),
+            order: Some(SortOrder::Asc),
+            ..Default::default()
+        });
+        assert_eq!(start_days(&records), vec![2, 3]);
+
+        // 退勤時刻の降順では、退勤前のレコードを先頭とする
+        let records = search(search_by_user::InputData {
+            sort: Some(SortKey::EndTime),
+            ..Default::default()
+        });
+        assert_eq!(records[0].id, open_record.id);
+        assert_eq!(start_days(&records[1..]), vec![3, 2, 1]);
+
+        let records = search(search_by_user::InputData {
+            open_only: true,
+            ..Default::default()
+        });
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, open_record.id);
+
+        let records = search(search_by_user::InputData {
+            has_note: true,
+            ..Default::default()
+        });
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].note, Some("直行直帰".to_string()));
+
+        // カーソルによるページ送り: 出勤中, 10/3 → 10/2, 10/1
+        let first_page = attendance_driver
+            .get_records(search_by_user::InputData {
+                uid: test_user.uid.clone(),
+                page: 1,
+                count: 2,
+                sort: Some(SortKey::StartTime),
+                with_total: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first_page.records[0].id, open_record.id);
+        assert!(first_page.has_more);
+        assert_eq!(first_page.total, Some(4));
+        // カーソルを指定した場合は出勤時刻順とする
+        let next_page = attendance_driver
+            .get_records(search_by_user::InputData {
+                uid: test_user.uid.clone(),
+                page: 1,
+                count: 2,
+                cursor: first_page.next_cursor,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(start_days(&next_page.records), vec![2, 1]);
+        assert!(!next_page.has_more);
+        assert_eq!(next_page.next_cursor, None);
+        assert_eq!(next_page.total, None);
+
+        // 出勤時刻順以外ではページ番号で送る
+        let id_page = attendance_driver
+            .get_records(search_by_user::InputData {
+                uid: test_user.uid.clone(),
+                page: 2,
+                count: 3,
+                sort: Some(SortKey::Id),
+                order: Some(SortOrder::Asc),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(id_page.records.len(), 1);
+        assert_eq!(id_page.records[0].id, open_record.id);
+        assert!(!id_page.has_more);
+        assert_eq!(id_page.next_cursor, None);
+    }
+
+    #[test]
+    fn overlap_test() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let date = |day: u32, hour: u32| Tokyo.ymd(2020, 10, day).and_hms(hour, 0, 0).timestamp();
+        let add = |start_time: i64, end_time: i64| {
+            attendance_driver.add_record(add::InputData {
+                uid: test_user.uid.clone(),
+                start_time,
+                end_time,
+                breaks: vec![],
+                note: None,
+            })
+        };
+
+        let first = add(date(1, 9), date(1, 18)).unwrap();
+        // 終了時刻と開始時刻が一致するレコードは重複しない
+        let second = add(date(1, 18), date(1, 20)).unwrap();
+
+        match add(date(1, 17), date(1, 19)) {
+            Err(DataAccessError::Conflict(message)) => {
+                assert_eq!(
+                    message,
+                    format!("Record overlaps with record {}!", first.id)
+                )
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        match add(date(1, 10), date(1, 11)) {
+            Err(DataAccessError::Conflict(message)) => {
+                assert!(message.contains(&first.id.to_string()))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let update_result = attendance_driver.update_record(update::InputData {
+            uid: test_user.uid.clone(),
+            id: second.id,
+            start_time: Some(date(1, 17)),
+            end_time: None,
+            breaks: None,
+            note: None,
+        });
+        assert!(matches!(update_result, Err(DataAccessError::Conflict(_))));
+        // 自身との重複は判定しない
+        let update_result = attendance_driver.update_record(update::InputData {
+            uid: test_user.uid.clone(),
+            id: second.id,
+            start_time: Some(date(1, 19)),
+            end_time: None,
+            breaks: None,
+            note: None,
+        });
+        assert!(update_result.is_ok());
+
+        // 検証を経ずに登録されたレコードも、排他制約により拒否する
+        let insert_result = diesel::insert_into(attendance_records::table)
+            .values(NewRecord {
+                user_id: test_user.id,
+                start_time: Utc.timestamp(date(1, 8), 0),
+                end_time: None,
+                note: None,
+            })
+            .execute(&connection)
+            .map_err(map_write_error);
+        assert!(matches!(insert_result, Err(DataAccessError::Conflict(_))));
+    }
+
+    #[test]
+    fn summarize_test() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let date = |month: u32, day: u32, hour: u32, min: u32| {
+            Tokyo
+                .ymd(2020, month, day)
+                .and_hms(hour, min, 0)
+                .timestamp()
+        };
+
+        // 10/1: 9:00-18:00(休憩1時間), 10/2: 9:00-12:30, 10/2: 13:30-18:00(休憩15分), 11/2: 9:00-17:00
+        let inputs = vec![
+            (
+                date(10, 1, 9, 0),
+                date(10, 1, 18, 0),
+                vec![(date(10, 1, 12, 0), date(10, 1, 13, 0))],
+            ),
+            (date(10, 2, 9, 0), date(10, 2, 12, 30), vec![]),
+            (
+                date(10, 2, 13, 30),
+                date(10, 2, 18, 0),
+                vec![(date(10, 2, 15, 0), date(10, 2, 15, 15))],
+            ),
+            (date(11, 2, 9, 0), date(11, 2, 17, 0), vec![]),
+        ];
+        for (start_time, end_time, breaks) in inputs {
+            attendance_driver
+                .add_record(add::InputData {
+                    uid: test_user.uid.clone(),
+                    start_time,
+                    end_time,
+                    breaks: breaks
+                        .into_iter()
+                        .map(|(start_time, end_time)| BreakInput {
+                            start_time,
+                            end_time,
+                        })
+                        .collect(),
+                    note: None,
+                })
+                .unwrap();
+        }
+
+        let by_day = attendance_driver
+            .summarize(summary::InputData {
+                uid: test_user.uid.clone(),
+                from: date(10, 1, 0, 0),
+                to: date(10, 31, 0, 0),
+                group_by: GroupBy::Day,
+            })
+            .unwrap();
+        assert_eq!(by_day.items.len(), 2);
+        assert_eq!(
+            by_day.items[0].start,
+            Tokyo.ymd(2020, 10, 1).and_hms(0, 0, 0)
+        );
+        assert_eq!(by_day.items[0].record_count, 1);
+        assert_eq!(by_day.items[0].worked_minutes, 8 * 60);
+        assert_eq!(by_day.items[0].break_minutes, 60);
+        assert_eq!(by_day.items[1].record_count, 2);
+        assert_eq!(by_day.items[1].worked_minutes, 3 * 60 + 30 + 4 * 60 + 15);
+        assert_eq!(by_day.items[1].break_minutes, 15);
+        assert_eq!(by_day.total.record_count, 3);
+        assert_eq!(by_day.total.worked_minutes, 8 * 60 + 7 * 60 + 45);
+        assert_eq!(by_day.total.break_minutes, 75);
+        assert_eq!(by_day.items[0].working_time.scheduled_minutes, 8 * 60);
+        assert_eq!(by_day.items[1].working_time.scheduled_minutes, 7 * 60 + 45);
+        assert_eq!(by_day.total.working_time.worked_minutes, 15 * 60 + 45);
+        assert_eq!(by_day.total.working_time.overtime_minutes, 0);
+
+        let by_month = attendance_driver
+            .summarize(summary::InputData {
+                uid: test_user.uid.clone(),
+                from: date(10, 1, 0, 0),
+                to: date(12, 1, 0, 0),
+                group_by: GroupBy::Month,
+            })
+            .unwrap();
+        assert_eq!(by_month.items.len(), 2);
+        assert_eq!(by_month.items[0].record_count, 3);
+        assert_eq!(
+            by_month.items[1].start,
+            Tokyo.ymd(2020, 11, 1).and_hms(0, 0, 0)
+        );
+        assert_eq!(by_month.items[1].worked_minutes, 8 * 60);
+        assert_eq!(by_month.total.record_count, 4);
+    }
+
+    #[test]
+    fn export_batch_test() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let other_user = UserDriver::new(&connection)
+            .create("export other user".to_string(), Some("UTC".to_string()))
+            .unwrap();
+        let date = |day: u32, hour: u32| Tokyo.ymd(2020, 10, day).and_hms(hour, 0, 0).timestamp();
+
+        for (uid, day) in &[
+            (&test_user.uid, 2),
+            (&other_user.uid, 1),
+            (&test_user.uid, 1),
+            (&test_user.uid, 3),
+        ] {
+            attendance_driver
+                .add_record(add::InputData {
+                    uid: uid.to_string(),
+                    start_time: date(*day, 9),
+                    end_time: date(*day, 19),
+                    breaks: vec![],
+                    note: None,
+                })
+                .unwrap();
+        }
+
+        let input = export::InputData {
+            uid: None,
+            from: date(1, 0),
+            to: date(4, 0),
+            columns: Column::ALL.to_vec(),
+        };
+        // ユーザー、出勤時刻の順に2件ずつ取得する
+        let mut exported = Vec::new();
+        let mut after = None;
+        loop {
+            let rows = attendance_driver.export_batch(&input, after, 2).unwrap();
+            after = rows.last().map(ExportCursor::of);
+            let is_last = rows.len() < 2;
+            exported.extend(rows);
+            if is_last {
+                break;
+            }
+        }
+        // 他のテストが登録したレコードは除く
+        let exported = exported
+            .into_iter()
+            .filter(|row| row.uid == test_user.uid || row.uid == other_user.uid)
+            .collect::<Vec<export::ExportRow>>();
+        let keys = exported
+            .iter()
+            .map(|row| (row.uid.as_str(), row.record.start_time.timestamp()))
+            .collect::<Vec<(&str, i64)>>();
+        assert_eq!(
+            keys,
+            vec![
+                (test_user.uid.as_str(), date(1, 9)),
+                (test_user.uid.as_str(), date(2, 9)),
+                (test_user.uid.as_str(), date(3, 9)),
+                (other_user.uid.as_str(), date(1, 9)),
+            ]
+        );
+        assert_eq!(exported[0].time_zone, Tokyo);
+        assert_eq!(exported[3].time_zone, chrono_tz::UTC);
+        // 1日8時間を超えた分は時間外労働
+        let working_time = exported[0].record.working_time.unwrap();
+        assert_eq!(working_time.worked_minutes, 10 * 60);
+        assert_eq!(working_time.overtime_minutes, 2 * 60);
+
+        let rows = attendance_driver
+            .export_batch(
+                &export::InputData {
+                    uid: Some(other_user.uid.clone()),
+                    ..input
+                },
+                None,
+                10,
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn import_records_test() {
+        let connection = test_util::connection_init();
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let body = format!(
+            "uid,start_time,end_time,breaks
+{uid},2020-08-03T09:00:00+09:00,2020-08-03T18:00:00+09:00,2020-08-03T12:00:00+09:00/2020-08-03T13:00:00+09:00
+{uid},2020-08-03T17:00:00+09:00,2020-08-03T20:00:00+09:00,
+unknown import user,2020-08-03T09:00:00+09:00,2020-08-03T18:00:00+09:00,
+{uid},2020-08-04T18:00:00+09:00,2020-08-04T09:00:00+09:00,
+{uid},2020-08-05T09:00:00+09:00,2020-08-05T18:00:00+09:00,
+",
+            uid = test_user.uid
+        );
+        let rows = || import::parse(&StandardCsv, &body, Utc::now().timestamp()).unwrap();
+        let count = || {
+            attendance_records::dsl::attendance_records
+                .filter(attendance_records::dsl::user_id.eq(test_user.id))
+                .count()
+                .get_result::<i64>(&connection)
+                .unwrap()
+        };
+
+        // 1行でも失敗した場合はすべて取り消す
+        let report = attendance_driver
+            .import_records(rows(), Mode::AllOrNothing)
+            .unwrap();
+        assert!(!report.committed);
+        assert_eq!((report.imported, report.failed), (0, 3));
+        assert!(report.rows.iter().all(|row| row.record_id.is_none()));
+        let failures = report
+            .rows
+            .iter()
+            .filter(|row| !row.errors.is_empty())
+            .map(|row| (row.line, row.errors[0].field.as_str()))
+            .collect::<Vec<(u64, &str)>>();
+        // 先に登録した行との重複、存在しないユーザー、開始と終了が逆
+        assert_eq!(
+            failures,
+            vec![(3, "start_time"), (4, "uid"), (5, "end_time")]
+        );
+        assert_eq!(count(), 0);
+
+        let report = attendance_driver
+            .import_records(rows(), Mode::SkipInvalid)
+            .unwrap();
+        assert!(report.committed);
+        assert_eq!((report.imported, report.failed), (2, 3));
+        assert!(report.rows[0].record_id.is_some());
+        assert_eq!(report.rows[0].uid, Some(test_user.uid.clone()));
+        assert_eq!(count(), 2);
+        let record = attendance_driver
+            .find_record(report.rows[0].record_id.unwrap())
+            .unwrap();
+        assert_eq!(record.break_seconds, 60 * 60);
+    }
 }
@@ -10,7 +10,7 @@ pub fn get_user(connection: &PgConnection, uid: String) -> Result<Option<User>,
         .filter(users::dsl::uid.eq(uid.clone()))
         .first::<User>(connection)
         .optional()
-        .or_else(|_| Err(DataAccessError::InternalError))
+        .map_err(DataAccessError::from)
 }
 
 // noinspection DuplicatedCode
@@ -22,17 +22,27 @@ pub fn get_registered_user(
         .filter(users::dsl::uid.eq(uid.clone()))
         .first::<User>(connection)
         .optional()
-        .or_else(|_| Err(DataAccessError::InternalError))?;
+        .map_err(DataAccessError::from)?;
 
     if target_user.is_none() {
-        return Err(DataAccessError::InternalErrorWithMessage(
-            "User not found!".to_string(),
-        ));
+        return Err(DataAccessError::NotFound("User not found!".to_string()));
     } else {
         Ok(target_user.unwrap())
     }
 }
 
+/// 勤怠記録を登録・変更できるユーザー. 無効化したユーザーはForbiddenとする.
+pub fn get_active_user(connection: &PgConnection, uid: String) -> Result<User, DataAccessError> {
+    let user = get_registered_user(connection, uid)?;
+    if user.is_active() {
+        Ok(user)
+    } else {
+        Err(DataAccessError::Forbidden(
+            "User is deactivated!".to_string(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -64,4 +74,20 @@ mod test {
         let get_user = get_registered_user(&connection, test_user.uid.clone()).unwrap();
         assert_eq!(get_user.uid, test_user.uid.clone());
     }
+
+    #[test]
+    fn get_active_user_test() {
+        let connection = test_util::connection_init();
+        let test_user = test_user_by_connection(&connection);
+        assert!(get_active_user(&connection, test_user.uid.clone()).is_ok());
+
+        diesel::update(users::dsl::users.find(test_user.id))
+            .set(users::dsl::deactivated_at.eq(Some(chrono::Utc::now())))
+            .execute(&connection)
+            .unwrap();
+        assert!(matches!(
+            get_active_user(&connection, test_user.uid.clone()),
+            Err(DataAccessError::Forbidden(_))
+        ));
+    }
 }
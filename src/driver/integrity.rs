@@ -0,0 +1,178 @@
+use crate::database_utils::error::{DataAccessError, UseCase};
+use crate::domain::entity::integrity_issue::IntegrityIssue;
+use crate::domain::entity::user::{validate_time_zone, User};
+use crate::schema::users::dsl;
+use crate::usecase::integrity::check::CheckIntegrityUseCase;
+use crate::usecase::validation::MAX_SHIFT_SECONDS;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Double, Int4, Nullable, Text};
+
+pub struct IntegrityDriver<'a> {
+    connection: &'a PgConnection,
+}
+
+impl<'a> IntegrityDriver<'a> {
+    pub fn new(connection: &'a PgConnection) -> IntegrityDriver<'a> {
+        IntegrityDriver { connection }
+    }
+}
+
+impl<'a> UseCase for IntegrityDriver<'a> {}
+
+#[derive(Debug, QueryableByName)]
+struct IssueRow {
+    #[sql_type = "Text"]
+    check_name: String,
+    #[sql_type = "Text"]
+    uid: String,
+    #[sql_type = "Nullable<Int4>"]
+    record_id: Option<i32>,
+    #[sql_type = "Text"]
+    detail: String,
+}
+
+impl IssueRow {
+    fn to_entity(&self) -> IntegrityIssue {
+        IntegrityIssue {
+            check: self.check_name.clone(),
+            uid: self.uid.clone(),
+            record_id: self.record_id,
+            detail: self.detail.clone(),
+        }
+    }
+}
+
+/// 重複や未退勤レコードの件数などはデータベースの制約で保証するため、
+/// ここでは制約で表現していない休憩と勤務時間の整合性を検査する.
+const ISSUE_QUERY: &str = "
+    SELECT 'break_outside_record' AS check_name, users.uid, records.id AS record_id,
+        format('break %s is outside of the record', breaks.id) AS detail
+    FROM breaks
+    JOIN attendance_records AS records ON records.id = breaks.record_id
+    JOIN users ON users.id = records.user_id
+    WHERE breaks.start_time < records.start_time
+        OR breaks.end_time > records.end_time
+    UNION ALL
+    SELECT 'open_break_on_closed_record', users.uid, records.id,
+        format('break %s is not finished', breaks.id)
+    FROM breaks
+    JOIN attendance_records AS records ON records.id = breaks.record_id
+    JOIN users ON users.id = records.user_id
+    WHERE breaks.end_time IS NULL AND records.end_time IS NOT NULL
+    UNION ALL
+    SELECT 'overlapping_breaks', users.uid, records.id,
+        format('break %s overlaps with break %s', a.id, b.id)
+    FROM breaks AS a
+    JOIN breaks AS b
+        ON a.record_id = b.record_id
+        AND a.id < b.id
+        AND tstzrange(a.start_time, a.end_time, '[)') && tstzrange(b.start_time, b.end_time, '[)')
+    JOIN attendance_records AS records ON records.id = a.record_id
+    JOIN users ON users.id = records.user_id
+    UNION ALL
+    SELECT 'shift_too_long', users.uid, records.id,
+        format('shift is %s hours', round(EXTRACT(EPOCH FROM (records.end_time - records.start_time)) / 3600))
+    FROM attendance_records AS records
+    JOIN users ON users.id = records.user_id
+    WHERE records.end_time - records.start_time > $1 * INTERVAL '1 second'
+    UNION ALL
+    SELECT 'stale_open_record', users.uid, records.id,
+        format('not clocked out since %s', records.start_time)
+    FROM attendance_records AS records
+    JOIN users ON users.id = records.user_id
+    WHERE records.end_time IS NULL
+        AND records.start_time < now() - $1 * INTERVAL '1 second'
+    ORDER BY uid, record_id, check_name
+";
+
+impl<'a> CheckIntegrityUseCase for IntegrityDriver<'a> {
+    fn check_integrity(&self) -> Result<Vec<IntegrityIssue>, DataAccessError> {
+        let rows = diesel::sql_query(ISSUE_QUERY)
+            .bind::<Double, _>(MAX_SHIFT_SECONDS as f64)
+            .load::<IssueRow>(self.connection);
+        let mut issues = self
+            .parse_data_access_result(rows)?
+            .iter()
+            .map(IssueRow::to_entity)
+            .collect::<Vec<IntegrityIssue>>();
+
+        // time zoneはchrono-tzで検証する
+        let users = self.parse_data_access_result(dsl::users.load::<User>(self.connection))?;
+        for user in users {
+            if let Err(message) = validate_time_zone(&user.time_zone) {
+                issues.push(IntegrityIssue {
+                    check: "invalid_time_zone".to_string(),
+                    uid: user.uid,
+                    record_id: None,
+                    detail: message,
+                });
+            }
+        }
+        issues.sort_by(|a, b| (&a.uid, a.record_id).cmp(&(&b.uid, b.record_id)));
+
+        Ok(issues)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util;
+    use crate::driver::users::test_utils::test_user_by_connection;
+    use diesel::sql_types::Timestamptz;
+
+    #[derive(QueryableByName)]
+    struct Id {
+        #[sql_type = "Int4"]
+        id: i32,
+    }
+
+    #[test]
+    fn check_integrity_test() {
+        let connection = test_util::connection_init();
+        let test_user = test_user_by_connection(&connection);
+        let driver = IntegrityDriver::new(&connection);
+        let before = driver.check_integrity().unwrap();
+
+        // アプリケーションの検証を経由せずに不整合なデータを登録する
+        let start = chrono::Utc::now() - chrono::Duration::days(3);
+        let record = diesel::sql_query(
+            "INSERT INTO attendance_records (user_id, start_time, end_time)
+             VALUES ($1, $2, $2 + INTERVAL '30 hours') RETURNING id",
+        )
+        .bind::<Int4, _>(test_user.id)
+        .bind::<Timestamptz, _>(start)
+        .get_result::<Id>(&connection)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO breaks (record_id, start_time, end_time)
+             VALUES ($1, $2 - INTERVAL '1 hour', $2), ($1, $2 + INTERVAL '1 hour', NULL)",
+        )
+        .bind::<Int4, _>(record.id)
+        .bind::<Timestamptz, _>(start)
+        .execute(&connection)
+        .unwrap();
+        diesel::update(dsl::users.find(test_user.id))
+            .set(dsl::time_zone.eq("Mars/Olympus_Mons"))
+            .execute(&connection)
+            .unwrap();
+
+        let issues = driver.check_integrity().unwrap();
+        let mut found = issues
+            .iter()
+            .filter(|issue| !before.contains(issue))
+            .map(|issue| (issue.check.as_str(), issue.record_id))
+            .collect::<Vec<(&str, Option<i32>)>>();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("break_outside_record", Some(record.id)),
+                ("invalid_time_zone", None),
+                ("open_break_on_closed_record", Some(record.id)),
+                ("shift_too_long", Some(record.id)),
+            ]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod api_keys;
 pub mod attendance_records;
 pub mod common;
+pub mod integrity;
+pub mod payroll;
 pub mod users;
@@ -0,0 +1,203 @@
+use crate::database_utils::error::{DataAccessError, UseCase};
+use crate::domain::entity::hourly_wage::HourlyWage;
+use crate::domain::entity::payroll::{calculate, wage_at, Payroll, PayrollEntry};
+use crate::domain::entity::working_time::{classify, local_date, local_instant, LaborRules};
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::driver::common::get_registered_user;
+use crate::schema::hourly_wages::{self, dsl};
+use crate::usecase::payroll::calculate::{self as calculate_payroll, CalculatePayrollUseCase};
+use crate::usecase::payroll::set_wage::{self, SetWageUseCase};
+use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+
+#[derive(Insertable)]
+#[table_name = "hourly_wages"]
+struct NewWage {
+    user_id: i32,
+    amount: i32,
+    effective_from: NaiveDate,
+}
+
+pub struct PayrollDriver<'a> {
+    connection: &'a PgConnection,
+}
+
+impl<'a> PayrollDriver<'a> {
+    pub fn new(connection: &'a PgConnection) -> PayrollDriver<'a> {
+        PayrollDriver { connection }
+    }
+}
+
+impl<'a> UseCase for PayrollDriver<'a> {}
+
+impl<'a> SetWageUseCase for PayrollDriver<'a> {
+    fn set_wage(&self, input: set_wage::InputData) -> Result<HourlyWage, DataAccessError> {
+        let user = get_registered_user(self.connection, input.uid)?;
+        if input.amount < 0 {
+            return Err(DataAccessError::validation(
+                "amount",
+                "must not be negative",
+            ));
+        }
+
+        let result = diesel::insert_into(hourly_wages::table)
+            .values(NewWage {
+                user_id: user.id,
+                amount: input.amount,
+                effective_from: input.effective_from,
+            })
+            .on_conflict((dsl::user_id, dsl::effective_from))
+            .do_update()
+            .set(dsl::amount.eq(input.amount))
+            .get_result::<HourlyWage>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
+impl<'a> CalculatePayrollUseCase for PayrollDriver<'a> {
+    fn calculate_payroll(
+        &self,
+        input: calculate_payroll::InputData,
+    ) -> Result<Payroll, DataAccessError> {
+        let user = get_registered_user(self.connection, input.uid)?;
+        let from = Utc.timestamp(input.from, 0);
+        let to = Utc.timestamp(input.to, 0);
+        let tz = user.tz();
+
+        // 週40時間・月60時間の判定のため、期間の開始を含む週と月の初めから読み込む
+        let rules = LaborRules::default();
+        let from_date = local_date(&tz, from);
+        let month_start = NaiveDate::from_ymd(from_date.year(), from_date.month(), 1);
+        let context_start = rules.week_of(from_date).min(month_start);
+        let records = AttendanceRecordDriver::new(self.connection).load_closed_records(
+            user.id,
+            local_instant(&tz, context_start.and_hms(0, 0, 0)),
+            to,
+        )?;
+        let working_times = classify(&records, &rules, &tz);
+
+        let wages: Vec<(NaiveDate, i64)> = dsl::hourly_wages
+            .filter(dsl::user_id.eq(user.id))
+            .load::<HourlyWage>(self.connection)
+            .map_err(DataAccessError::from)?
+            .iter()
+            .map(|wage| (wage.effective_from, wage.amount as i64))
+            .collect();
+
+        let mut entries = vec![];
+        for record in records.iter() {
+            // 時給の適用日・月の判定はユーザーのtime zoneにおける日時で行う
+            let start_time = record.start_time.with_timezone(&tz).naive_local();
+            let hourly_wage = wage_at(&wages, start_time.date()).ok_or_else(|| {
+                DataAccessError::Conflict(format!(
+                    "Hourly wage is not registered at {}!",
+                    start_time.date()
+                ))
+            })?;
+            entries.push(PayrollEntry {
+                start_time,
+                working_time: working_times[&record.id],
+                hourly_wage,
+                in_period: record.start_time >= from,
+            });
+        }
+
+        calculate(&entries, &input.rates).map_err(DataAccessError::Conflict)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util;
+    use crate::domain::entity::payroll::{PayKind, PremiumRates};
+    use crate::driver::users::test_utils::test_user_by_connection;
+    use crate::usecase::attendance_records::add::{self, AddRecordUseCase, BreakInput};
+    use chrono_tz::Asia::Tokyo;
+
+    #[test]
+    fn payroll_driver_scenario() {
+        let connection = test_util::connection_init();
+        let payroll_driver = PayrollDriver::new(&connection);
+        let attendance_driver = AttendanceRecordDriver::new(&connection);
+        let test_user = test_user_by_connection(&connection);
+        let datetime = |month: u32, day: u32| Tokyo.ymd(2020, month, day).and_hms(9, 0, 0);
+
+        // 9/30: 9:00-18:00(期間外), 10/1: 9:00-18:00, 10/2: 9:00-20:00(いずれも休憩1時間)
+        let shifts = vec![
+            (datetime(9, 30), 9),
+            (datetime(10, 1), 9),
+            (datetime(10, 2), 11),
+        ];
+        for (start_time, hours) in shifts {
+            let start_time = start_time.timestamp();
+            attendance_driver
+                .add_record(add::InputData {
+                    uid: test_user.uid.clone(),
+                    start_time,
+                    end_time: start_time + hours * 60 * 60,
+                    breaks: vec![BreakInput {
+                        start_time: start_time + 3 * 60 * 60,
+                        end_time: start_time + 4 * 60 * 60,
+                    }],
+                    note: None,
+                })
+                .unwrap();
+        }
+
+        let input = || calculate_payroll::InputData {
+            uid: test_user.uid.clone(),
+            from: Tokyo.ymd(2020, 10, 1).and_hms(0, 0, 0).timestamp(),
+            to: Tokyo.ymd(2020, 11, 1).and_hms(0, 0, 0).timestamp(),
+            rates: PremiumRates::default(),
+        };
+
+        // 時給が未登録
+        assert!(payroll_driver.calculate_payroll(input()).is_err());
+
+        payroll_driver
+            .set_wage(set_wage::InputData {
+                uid: test_user.uid.clone(),
+                amount: 1000,
+                effective_from: NaiveDate::from_ymd(2020, 9, 1),
+            })
+            .unwrap();
+        let wage = payroll_driver
+            .set_wage(set_wage::InputData {
+                uid: test_user.uid.clone(),
+                amount: 1200,
+                effective_from: NaiveDate::from_ymd(2020, 10, 2),
+            })
+            .unwrap();
+        assert_eq!(wage.amount, 1200);
+
+        let payroll = payroll_driver.calculate_payroll(input()).unwrap();
+        let base_items: Vec<(i64, i64)> = payroll
+            .items
+            .iter()
+            .filter(|item| item.kind == PayKind::Base)
+            .map(|item| (item.hourly_wage, item.minutes))
+            .collect();
+        assert_eq!(base_items, vec![(1000, 480), (1200, 600)]);
+        let overtime = payroll
+            .items
+            .iter()
+            .find(|item| item.kind == PayKind::Overtime)
+            .unwrap();
+        assert_eq!((overtime.hourly_wage, overtime.minutes), (1200, 120));
+        assert_eq!(payroll.gross_amount, 8000 + 12000 + 600);
+
+        // 同じ適用開始日は上書き
+        payroll_driver
+            .set_wage(set_wage::InputData {
+                uid: test_user.uid.clone(),
+                amount: 1100,
+                effective_from: NaiveDate::from_ymd(2020, 10, 2),
+            })
+            .unwrap();
+        let payroll = payroll_driver.calculate_payroll(input()).unwrap();
+        assert_eq!(payroll.gross_amount, 8000 + 11000 + 550);
+    }
+}
@@ -1,10 +1,14 @@
 use crate::database_utils::error::{DataAccessError, UseCase};
-use crate::domain::entity::user::User;
+use crate::domain::entity::user::{User, DEFAULT_TIME_ZONE};
 use crate::driver::common::{get_registered_user, get_user};
 use crate::schema::users::{self, dsl};
 use crate::usecase::users::add::CreateUserUseCase;
 use crate::usecase::users::check::CheckUserUseCase;
+use crate::usecase::users::deactivate::DeactivateUserUseCase;
 use crate::usecase::users::delete::DeleteUserUseCase;
+use crate::usecase::users::list::ListUsersUseCase;
+use crate::usecase::users::update::UpdateUserUseCase;
+use chrono::Utc;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 
@@ -12,11 +16,15 @@ use diesel::prelude::*;
 #[table_name = "users"]
 struct NewUser {
     uid: String,
+    time_zone: String,
 }
 
 impl NewUser {
-    pub fn new(uid: String) -> NewUser {
-        NewUser { uid }
+    pub fn new(uid: String, time_zone: Option<String>) -> NewUser {
+        NewUser {
+            uid,
+            time_zone: time_zone.unwrap_or_else(|| DEFAULT_TIME_ZONE.name().to_string()),
+        }
     }
 }
 
@@ -33,15 +41,15 @@ impl<'a> UserDriver<'a> {
 impl<'a> UseCase for UserDriver<'a> {}
 
 impl<'a> CreateUserUseCase for UserDriver<'a> {
-    fn create(&self, uid: String) -> Result<User, DataAccessError> {
+    fn create(&self, uid: String, time_zone: Option<String>) -> Result<User, DataAccessError> {
         let user = get_user(self.connection, uid.clone())?;
         if user.is_some() {
-            return Err(DataAccessError::InternalErrorWithMessage(
+            return Err(DataAccessError::Conflict(
                 "Specified id is already exist!".to_string(),
             ));
         }
 
-        let new_user = NewUser::new(uid);
+        let new_user = NewUser::new(uid, time_zone);
 
         let result = diesel::insert_into(users::table)
             .values(new_user)
@@ -51,6 +59,18 @@ impl<'a> CreateUserUseCase for UserDriver<'a> {
     }
 }
 
+impl<'a> UpdateUserUseCase for UserDriver<'a> {
+    fn update_time_zone(&self, uid: String, time_zone: String) -> Result<User, DataAccessError> {
+        let user = get_registered_user(self.connection, uid)?;
+
+        let result = diesel::update(dsl::users.find(user.id))
+            .set(dsl::time_zone.eq(time_zone))
+            .get_result::<User>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
 impl<'a> DeleteUserUseCase for UserDriver<'a> {
     fn delete(&self, uid: String) -> Result<(), DataAccessError> {
         // user registered check
@@ -60,7 +80,7 @@ impl<'a> DeleteUserUseCase for UserDriver<'a> {
 
         match result {
             Ok(_) => Ok(()),
-            Err(_) => Err(DataAccessError::InternalError),
+            Err(error) => Err(DataAccessError::from(error)),
         }
     }
 }
@@ -73,6 +93,31 @@ impl<'a> CheckUserUseCase for UserDriver<'a> {
     }
 }
 
+impl<'a> ListUsersUseCase for UserDriver<'a> {
+    fn list_users(&self) -> Result<Vec<User>, DataAccessError> {
+        let result = dsl::users
+            .order(dsl::id.asc())
+            .load::<User>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
+impl<'a> DeactivateUserUseCase for UserDriver<'a> {
+    fn deactivate(&self, uid: String) -> Result<User, DataAccessError> {
+        let user = get_registered_user(self.connection, uid)?;
+        if !user.is_active() {
+            return Ok(user);
+        }
+
+        let result = diesel::update(dsl::users.find(user.id))
+            .set(dsl::deactivated_at.eq(Some(Utc::now())))
+            .get_result::<User>(self.connection);
+
+        self.parse_data_access_result(result)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -84,13 +129,27 @@ mod test {
         let user_driver = UserDriver::new(&connection);
         let test_uid = "asdfghjkl";
 
-        let created_posts1 = user_driver.create(test_uid.to_string()).unwrap();
+        let created_posts1 = user_driver.create(test_uid.to_string(), None).unwrap();
         assert_eq!(created_posts1.uid, test_uid.to_string());
+        assert_eq!(created_posts1.time_zone, "Asia/Tokyo");
+
+        let updated = user_driver
+            .update_time_zone(test_uid.to_string(), "Europe/London".to_string())
+            .unwrap();
+        assert_eq!(updated.time_zone, "Europe/London");
 
         let user_is_registered = user_driver.check_user(test_uid.to_string()).unwrap();
         assert!(user_is_registered.is_some());
         assert_eq!(user_is_registered.unwrap().uid, test_uid.to_string());
 
+        let users = user_driver.list_users().unwrap();
+        assert!(users.iter().any(|user| user.uid == test_uid));
+
+        let deactivated = user_driver.deactivate(test_uid.to_string()).unwrap();
+        assert!(!deactivated.is_active());
+        let again = user_driver.deactivate(test_uid.to_string()).unwrap();
+        assert_eq!(again.deactivated_at, deactivated.deactivated_at);
+
         let delete = user_driver.delete(test_uid.to_string());
         assert!(delete.is_ok());
 
@@ -114,7 +173,7 @@ pub mod test_utils {
         let user_is_registered = user_driver.check_user(test_uid.to_string()).unwrap();
         match user_is_registered {
             Some(user) => user,
-            None => user_driver.create(test_uid.to_string()).unwrap(),
+            None => user_driver.create(test_uid.to_string(), None).unwrap(),
         }
     }
 
@@ -125,7 +184,7 @@ pub mod test_utils {
         let user_is_registered = user_driver.check_user(test_uid.to_string()).unwrap();
         match user_is_registered {
             Some(user) => user,
-            None => user_driver.create(test_uid.to_string()).unwrap(),
+            None => user_driver.create(test_uid.to_string(), None).unwrap(),
         }
     }
 }
@@ -1,9 +1,17 @@
 #[macro_use]
 extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
 
+pub mod admin;
+pub mod auth;
+pub mod config;
 pub mod database_utils;
 pub mod domain;
 pub mod driver;
+pub mod logging;
+pub mod metrics;
+pub mod rate_limit;
 pub mod routes;
 pub mod schema;
 pub mod usecase;
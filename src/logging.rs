@@ -0,0 +1,319 @@
+use crate::metrics;
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::HeaderMap;
+use actix_web::Error;
+use chrono::{SecondsFormat, Utc};
+use futures::future::{ok, Ready};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::io::Write;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// クライアントから受け取るrequest idの最大長.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// リクエスト毎にログへ付与する情報.
+struct RequestContext {
+    request_id: String,
+    method: String,
+    route: String,
+    uid: RefCell<Option<String>>,
+    status: Cell<Option<u16>>,
+    latency_ms: Cell<Option<f64>>,
+}
+
+tokio::task_local! {
+    static CONTEXT: RequestContext;
+}
+
+/// 1行のログ.
+#[derive(Serialize)]
+struct LogLine<'a> {
+    timestamp: String,
+    level: String,
+    target: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<f64>,
+}
+
+impl<'a> LogLine<'a> {
+    fn new(record: &'a log::Record) -> LogLine<'a> {
+        let mut line = LogLine {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level: record.level().to_string(),
+            target: record.target(),
+            message: record.args().to_string(),
+            request_id: None,
+            method: None,
+            route: None,
+            uid: None,
+            status: None,
+            latency_ms: None,
+        };
+        // リクエストの処理外(起動時など)のログには付与しない
+        let _ = CONTEXT.try_with(|context| {
+            line.request_id = Some(context.request_id.clone());
+            line.method = Some(context.method.clone());
+            line.route = Some(context.route.clone());
+            line.uid = context.uid.borrow().clone();
+            line.status = context.status.get();
+            line.latency_ms = context.latency_ms.get();
+        });
+        line
+    }
+}
+
+/// ログを1行1つのJSONで出力する.
+///
+/// 出力するレベルは`RUST_LOG`で指定し、未指定の場合は`info`とする.
+pub fn init() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format(|buf, record| {
+            let line = serde_json::to_string(&LogLine::new(record)).unwrap_or_default();
+            writeln!(buf, "{}", line)
+        })
+        .init();
+}
+
+/// 処理中のリクエストのid.
+pub fn current_request_id() -> Option<String> {
+    CONTEXT.try_with(|context| context.request_id.clone()).ok()
+}
+
+/// 処理中のリクエストのユーザーをログに付与する.
+pub fn set_uid(uid: &str) {
+    let _ = CONTEXT.try_with(|context| context.uid.replace(Some(uid.to_string())));
+}
+
+/// クライアントが指定したrequest idを使用できるか.
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c))
+}
+
+fn insert_request_id(headers: &mut HeaderMap, request_id: &str) {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+}
+
+/// request idを割り当ててログに付与し、レスポンスの`X-Request-Id`ヘッダーで返すmiddleware.
+///
+/// 内側のmiddlewareが返すエラーも記録するため、最も外側に配置する.
+pub struct RequestLogger;
+
+impl<S, B> Transform<S> for RequestLogger
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestLoggerMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestLoggerMiddleware { service })
+    }
+}
+
+pub struct RequestLoggerMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestLoggerMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| is_valid_request_id(value))
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let context = RequestContext {
+            request_id: request_id.clone(),
+            method: req.method().to_string(),
+            route: metrics::route_label(&req),
+            uid: RefCell::new(None),
+            status: Cell::new(None),
+            latency_ms: Cell::new(None),
+        };
+        let fut = self.service.call(req);
+
+        Box::pin(CONTEXT.scope(context, async move {
+            let result = match fut.await {
+                Ok(mut res) => {
+                    insert_request_id(res.headers_mut(), &request_id);
+                    Ok(res)
+                }
+                // rate limiterなどのエラーもrequest idを付けて返す
+                Err(e) => {
+                    let mut response = e.as_response_error().error_response();
+                    insert_request_id(response.headers_mut(), &request_id);
+                    Err(response)
+                }
+            };
+
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(response) => response.status(),
+            };
+            CONTEXT.with(|context| {
+                context.status.set(Some(status.as_u16()));
+                context
+                    .latency_ms
+                    .set(Some(started.elapsed().as_secs_f64() * 1000.0));
+            });
+            log::info!("request completed");
+            result.map_err(Error::from)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::routes::problem::Problem;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App, HttpResponse};
+
+    async fn problem_route() -> HttpResponse {
+        set_uid("asdfghjkl");
+        Problem::new(StatusCode::CONFLICT, None).to_response()
+    }
+
+    #[test]
+    fn is_valid_request_id_test() {
+        assert!(is_valid_request_id("3b2c6f3e-5b1d-4c53-9d7e-1f0b2c3d4e5f"));
+        assert!(is_valid_request_id("lb.1234:abc_def"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("a b"));
+        assert!(!is_valid_request_id("\"><script>"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+    }
+
+    #[actix_rt::test]
+    async fn request_id_test() {
+        let mut app = test::init_service(
+            App::new()
+                .wrap(RequestLogger)
+                .route("/problem", web::get().to(problem_route)),
+        )
+        .await;
+
+        // 指定されたidをそのまま使用する
+        let req = test::TestRequest::get()
+            .uri("/problem")
+            .header(REQUEST_ID_HEADER, "support-1234")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(
+            resp.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "support-1234"
+        );
+        let problem: Problem = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(problem.request_id, Some("support-1234".to_string()));
+
+        // 不正なidは新しく割り当てる
+        let req = test::TestRequest::get()
+            .uri("/problem")
+            .header(REQUEST_ID_HEADER, "a b")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        let request_id = resp
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(Uuid::parse_str(request_id).is_ok());
+
+        // ルーティングされないリクエストにも付与する
+        let req = test::TestRequest::get().uri("/none").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 404);
+        assert!(resp.headers().contains_key(REQUEST_ID_HEADER));
+    }
+
+    /// 内側のmiddlewareがエラーを返した場合.
+    #[actix_rt::test]
+    async fn middleware_error_test() {
+        let mut app = test::init_service(
+            App::new().wrap(RequestLogger).service(
+                web::scope("")
+                    .wrap_fn(|_, _| {
+                        futures::future::err(HttpResponse::TooManyRequests().finish().into())
+                    })
+                    .route("/limited", web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/limited")
+            .header(REQUEST_ID_HEADER, "support-1234")
+            .to_request();
+        let resp = app.call(req).await;
+        let error = resp.err().unwrap();
+        let resp = error.as_response_error().error_response();
+        assert_eq!(resp.status().as_u16(), 429);
+        assert_eq!(
+            resp.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "support-1234"
+        );
+    }
+
+    #[test]
+    fn log_line_test() {
+        let record = log::Record::builder()
+            .args(format_args!("hello"))
+            .level(log::Level::Warn)
+            .target("at_api")
+            .build();
+        let line = serde_json::to_value(LogLine::new(&record)).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "hello");
+        // リクエストの処理外
+        assert!(line.get("request_id").is_none());
+    }
+}
@@ -1,65 +1,181 @@
 extern crate at_api;
 
-use actix_ratelimit::errors::ARError::IdentificationError;
-use actix_ratelimit::{MemoryStore, MemoryStoreActor, RateLimiter};
-use actix_web::{middleware::Logger, web, App, HttpServer};
-use diesel::pg::PgConnection;
-use diesel::r2d2::{self, ConnectionManager};
-use at_api::database_utils::pool::env_database_url;
+use actix_web::{web, App, HttpServer};
+use at_api::admin::{self, AdminError};
+use at_api::auth::jwt::Verifier;
+use at_api::auth::Authentication;
+use at_api::config::{DatabaseSettings, Settings};
+use at_api::database_utils::migrations;
+use at_api::database_utils::pool::build_pool;
+use at_api::logging::{self, RequestLogger};
+use at_api::metrics::RequestMetrics;
+use at_api::rate_limit::{RateLimitStore, RateLimiter};
 use at_api::routes;
+use diesel::{Connection, PgConnection};
 use std::env;
-use std::time::Duration;
+use std::process;
+use std::thread;
 
-fn api_key_validate(key: &str) -> bool {
-    let env_key = env::var("X_API_KEY").expect("X_API_KEY must be set");
-    key == env_key
+/// 管理用のコマンドを実行する.
+///
+/// `at-api admin [--format table|json] <command>`
+fn admin(args: &[String]) {
+    let settings = load_settings();
+    let connection = connect(&settings.database);
+    match admin::run(&connection, &settings, args) {
+        Ok((output, success)) => {
+            println!("{}", output);
+            if !success {
+                process::exit(1);
+            }
+        }
+        Err(e @ AdminError::Usage(_)) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// migrationを実行する. 設定はデータベースの項目のみを読み込む.
+///
+/// `at-api migrate up|down|status`
+fn migrate(args: &[String]) {
+    let database = DatabaseSettings::load().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let connection = connect(&database);
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("up") => {
+            let mut output = Vec::new();
+            let result = migrations::run_pending(&connection, &mut output);
+            print!("{}", String::from_utf8_lossy(&output));
+            if result.is_ok() && output.is_empty() {
+                println!("no pending migrations");
+            }
+            result
+        }
+        Some("down") => migrations::revert_latest(&connection).map(|reverted| {
+            if reverted.is_none() {
+                println!("no applied migrations");
+            }
+        }),
+        Some("status") => migrations::status(&connection).map(|status| {
+            for (migration, applied) in status {
+                let mark = if applied { "X" } else { " " };
+                println!("[{}] {}", mark, diesel_migrations::name(&*migration));
+            }
+        }),
+        _ => {
+            eprintln!("usage: at-api migrate up|down|status");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// CLI用にデータベースへ接続する.
+fn connect(database: &DatabaseSettings) -> PgConnection {
+    PgConnection::establish(&database.url).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+/// 設定を読み込む. 不正な場合は内容を表示して終了する.
+fn load_settings() -> Settings {
+    Settings::load().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
 }
 
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init();
+    logging::init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|arg| arg.as_str()) {
+        Some("admin") => {
+            admin(&args[1..]);
+            return Ok(());
+        }
+        Some("migrate") => {
+            migrate(&args[1..]);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let settings = load_settings();
+
+    let pool = build_pool(&settings.database.url, &settings.database.pool);
 
-    let database_url = env_database_url();
+    // 実行中は/readyzが503を返すため、完了するまでトラフィックを受けない
+    if settings.database.run_migrations {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let result = pool
+                .get()
+                .map_err(|e| e.to_string())
+                .and_then(|connection| {
+                    migrations::run_pending(&connection, &mut output).map_err(|e| e.to_string())
+                });
+            for line in String::from_utf8_lossy(&output).lines() {
+                log::info!("{}", line);
+            }
+            if let Err(e) = result {
+                log::error!("failed to run migrations: {}", e);
+            }
+        });
+    }
 
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create pool.");
+    let verifier = web::Data::new(Verifier::new(settings.jwt.clone()).unwrap_or_else(|e| {
+        eprintln!("failed to load JWKS: {}", e);
+        process::exit(1);
+    }));
 
-    let port: i32 = env::var("PORT")
-        .unwrap_or_else(|_| "8088".to_string())
-        .parse()
-        .expect("PORT must be a number");
+    // 全workerで件数を共有する
+    let rate_limit_store = RateLimitStore::new();
 
-    let bind = format!("0.0.0.0:{}", port);
+    let bind = format!("0.0.0.0:{}", settings.server.port);
 
-    println!("Starting server at: {}", &bind);
+    log::info!("Starting server at: {}", &bind);
 
     HttpServer::new(move || {
-        let store = MemoryStore::new();
         App::new()
             .data(pool.clone())
-            .wrap(Logger::default())
-            .wrap(
-                RateLimiter::new(MemoryStoreActor::from(store.clone()).start())
-                    // 60秒以内のリクエスト件数を制限
-                    .with_interval(Duration::from_secs(60))
-                    .with_max_requests(100)
-                    // api keyが一致しているかvalidation
-                    .with_identifier(|req| {
-                        let key = req.headers().get("x-api-key").unwrap();
-                        let key = key.to_str().unwrap();
-                        if api_key_validate(key) {
-                            Ok(key.to_string())
-                        } else {
-                            println!("[api app log] x-api-key identification failed");
-                            Err(IdentificationError)
-                        }
-                    }),
+            .data(settings.clone())
+            .app_data(verifier.clone())
+            .wrap(RequestMetrics)
+            .wrap(RequestLogger)
+            .app_data(routes::json_config(&settings.server))
+            // ヘルスチェックとメトリクスはapi keyなしで応答する
+            .configure(routes::health::config)
+            .configure(routes::metrics::config)
+            .service(
+                web::scope("")
+                    // 認証済みのapi key毎・ユーザー毎に制限
+                    .wrap(RateLimiter::new(
+                        settings.rate_limit.clone(),
+                        rate_limit_store.clone(),
+                    ))
+                    .wrap(Authentication)
+                    .service(web::scope("/users").configure(routes::users::config))
+                    .service(
+                        web::scope("/attendance_records").configure(routes::attendances::config),
+                    )
+                    .service(web::scope("/payroll").configure(routes::payroll::config))
+                    .service(web::scope("/api_keys").configure(routes::api_keys::config)),
             )
-            .data(web::JsonConfig::default().limit(4096))
-            .service(web::scope("/users").configure(routes::users::config))
-            .service(web::scope("/attendance_records").configure(routes::attendances::config))
     })
     .bind(&bind)?
     .run()
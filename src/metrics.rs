@@ -0,0 +1,182 @@
+use crate::database_utils::pool::DbPool;
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::Error;
+use futures::future::{ok, Ready};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, Encoder, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    TextEncoder,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// ルーティングされなかったリクエストのroute label.
+/// 存在しないpathごとに系列が増えないようにまとめる.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+lazy_static! {
+    pub static ref HTTP_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "http_requests_total",
+        "Number of HTTP requests.",
+        &["method", "route", "status"]
+    )
+    .unwrap();
+    pub static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latencies in seconds.",
+        &["method", "route"]
+    )
+    .unwrap();
+    pub static ref RATE_LIMIT_REJECTIONS: IntCounter = register_int_counter!(
+        "rate_limit_rejections_total",
+        "Number of requests rejected by the rate limiter."
+    )
+    .unwrap();
+    pub static ref DB_POOL_CONNECTIONS: IntGauge = register_int_gauge!(
+        "db_pool_connections",
+        "Number of connections managed by the pool."
+    )
+    .unwrap();
+    pub static ref DB_POOL_IDLE_CONNECTIONS: IntGauge = register_int_gauge!(
+        "db_pool_idle_connections",
+        "Number of idle connections in the pool."
+    )
+    .unwrap();
+    pub static ref DB_POOL_WAIT_DURATION: Histogram = register_histogram!(
+        "db_pool_wait_seconds",
+        "Time spent waiting to check out a connection."
+    )
+    .unwrap();
+    pub static ref DB_POOL_TIMEOUTS: IntCounter = register_int_counter!(
+        "db_pool_timeouts_total",
+        "Number of connection checkouts that failed."
+    )
+    .unwrap();
+    pub static ref ATTENDANCE_RECORDS: IntCounterVec = register_int_counter_vec!(
+        "attendance_records_total",
+        "Number of attendance record operations.",
+        &["operation"]
+    )
+    .unwrap();
+}
+
+/// 勤怠記録に対する操作.
+pub enum RecordOperation {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl RecordOperation {
+    fn label(&self) -> &'static str {
+        match self {
+            RecordOperation::Created => "created",
+            RecordOperation::Updated => "updated",
+            RecordOperation::Deleted => "deleted",
+        }
+    }
+}
+
+pub fn record_operation(operation: RecordOperation) {
+    ATTENDANCE_RECORDS
+        .with_label_values(&[operation.label()])
+        .inc();
+}
+
+/// Prometheusのtext formatで出力する.
+pub fn render(pool: &DbPool) -> String {
+    let state = pool.state();
+    DB_POOL_CONNECTIONS.set(i64::from(state.connections));
+    DB_POOL_IDLE_CONNECTIONS.set(i64::from(state.idle_connections));
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// メトリクスとログに使用するroute.
+///
+/// pathにパラメーターを含むrouteは無いため、登録済みのpathはそのまま使用する.
+pub fn route_label(req: &ServiceRequest) -> String {
+    if req.resource_map().has_resource(req.path()) {
+        req.path().to_string()
+    } else {
+        UNMATCHED_ROUTE.to_string()
+    }
+}
+
+/// route, method, statusごとのリクエスト数とlatencyを記録するmiddleware.
+///
+/// rate limiterなど内側のmiddlewareが返すエラーも記録できるよう、最も外側に配置する.
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S> for RequestMetrics
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestMetricsMiddleware { service })
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestMetricsMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let method = req.method().to_string();
+        let route = route_label(&req);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().error_response().status(),
+            };
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                RATE_LIMIT_REJECTIONS.inc();
+            }
+
+            HTTP_REQUESTS
+                .with_label_values(&[&method, &route, status.as_str()])
+                .inc();
+            HTTP_REQUEST_DURATION
+                .with_label_values(&[&method, &route])
+                .observe(started.elapsed().as_secs_f64());
+            result
+        })
+    }
+}
@@ -0,0 +1,409 @@
+//! api key毎・ユーザー毎のリクエスト件数の制限.
+//!
+//! 一定時間(window)毎のリクエスト件数を数え、上限を超えたリクエストには429を返す.
+//! 件数は全workerで共有する`RateLimitStore`に保持する.
+use crate::auth;
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
+use crate::routes::problem::Problem;
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{Error, HttpResponse, ResponseError};
+use futures::future::{ok, Ready};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+pub const LIMIT_HEADER: &str = "x-ratelimit-limit";
+pub const REMAINING_HEADER: &str = "x-ratelimit-remaining";
+pub const RESET_HEADER: &str = "x-ratelimit-reset";
+
+/// リクエスト件数の上限の設定.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub interval: Duration,
+    /// api key毎・ユーザー毎の上限の初期値
+    pub max_requests: u32,
+    /// api keyのid毎の上限
+    pub api_keys: HashMap<i32, u32>,
+    /// uid毎の上限
+    pub uids: HashMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            interval: Duration::from_secs(60),
+            max_requests: 100,
+            api_keys: HashMap::new(),
+            uids: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn api_key_limit(&self, id: i32) -> u32 {
+        *self.api_keys.get(&id).unwrap_or(&self.max_requests)
+    }
+
+    fn uid_limit(&self, uid: &str) -> u32 {
+        *self.uids.get(uid).unwrap_or(&self.max_requests)
+    }
+}
+
+/// 制限の対象毎の件数.
+#[derive(Debug)]
+struct Window {
+    started_at: Instant,
+    count: u32,
+}
+
+/// リクエスト後の残り件数.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quota {
+    pub limit: u32,
+    pub remaining: u32,
+    /// 件数がリセットされるまでの時間
+    pub reset: Duration,
+}
+
+impl Quota {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        let values = [
+            (LIMIT_HEADER, self.limit as u64),
+            (REMAINING_HEADER, self.remaining as u64),
+            (RESET_HEADER, reset_seconds(self.reset)),
+        ];
+        for (name, value) in values.iter() {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from(*value));
+        }
+    }
+}
+
+/// 秒単位に切り上げる.
+fn reset_seconds(reset: Duration) -> u64 {
+    reset.as_secs() + if reset.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+/// 全workerで共有するリクエスト件数.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitStore {
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+#[derive(Debug, Default)]
+struct StoreInner {
+    windows: HashMap<String, Window>,
+    purged_at: Option<Instant>,
+}
+
+impl RateLimitStore {
+    pub fn new() -> RateLimitStore {
+        RateLimitStore::default()
+    }
+
+    /// 対象毎の件数を1つ増やす.
+    ///
+    /// いずれかの対象が上限に達している場合は件数を増やさず、その対象の`Quota`を`Err`で返す.
+    /// 成功した場合は残り件数が最も少ない対象の`Quota`を返す.
+    pub fn hit(
+        &self,
+        keys: &[(String, u32)],
+        interval: Duration,
+        now: Instant,
+    ) -> Result<Quota, Quota> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.purge(interval, now);
+
+        let mut quotas = Vec::with_capacity(keys.len());
+        for (key, limit) in keys {
+            let window = inner.windows.entry(key.clone()).or_insert(Window {
+                started_at: now,
+                count: 0,
+            });
+            if now.duration_since(window.started_at) >= interval {
+                window.started_at = now;
+                window.count = 0;
+            }
+            let quota = Quota {
+                limit: *limit,
+                remaining: limit.saturating_sub(window.count),
+                reset: interval - now.duration_since(window.started_at),
+            };
+            if quota.remaining == 0 {
+                return Err(quota);
+            }
+            quotas.push(quota);
+        }
+
+        for (key, _) in keys {
+            if let Some(window) = inner.windows.get_mut(key) {
+                window.count += 1;
+            }
+        }
+        let quota = quotas
+            .into_iter()
+            .map(|quota| Quota {
+                remaining: quota.remaining - 1,
+                ..quota
+            })
+            .min_by_key(|quota| quota.remaining)
+            .unwrap_or(Quota {
+                limit: 0,
+                remaining: 0,
+                reset: interval,
+            });
+        Ok(quota)
+    }
+}
+
+impl StoreInner {
+    /// 期限切れのwindowを定期的に削除する.
+    fn purge(&mut self, interval: Duration, now: Instant) {
+        match self.purged_at {
+            Some(purged_at) if now.duration_since(purged_at) < interval => {}
+            _ => {
+                self.windows
+                    .retain(|_, window| now.duration_since(window.started_at) < interval);
+                self.purged_at = Some(now);
+            }
+        }
+    }
+}
+
+/// 上限を超えたリクエストのエラー.
+#[derive(Debug)]
+pub struct RateLimitExceeded {
+    pub quota: Quota,
+}
+
+impl fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rate limit of {} requests exceeded! Retry after {} seconds.",
+            self.quota.limit,
+            reset_seconds(self.quota.reset)
+        )
+    }
+}
+
+impl ResponseError for RateLimitExceeded {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = Problem::new(self.status_code(), Some(self.to_string())).to_response();
+        let headers = response.headers_mut();
+        self.quota.insert_headers(headers);
+        headers.insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(reset_seconds(self.quota.reset)),
+        );
+        response
+    }
+}
+
+/// 認証済みのapi key毎と、ID tokenのユーザー毎にリクエスト件数を制限するmiddleware.
+///
+/// `Authentication`の内側に置く.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    store: RateLimitStore,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, store: RateLimitStore) -> RateLimiter {
+        RateLimiter {
+            config: Arc::new(config),
+            store,
+        }
+    }
+}
+
+impl<S, B> Transform<S> for RateLimiter
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimiterMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimiterMiddleware {
+            service: Rc::new(RefCell::new(service)),
+            limiter: self.clone(),
+        })
+    }
+}
+
+pub struct RateLimiterMiddleware<S> {
+    service: Rc<RefCell<S>>,
+    limiter: RateLimiter,
+}
+
+impl<S, B> Service for RateLimiterMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let api_key = auth::api_key(&req).ok_or_else(|| {
+                DataAccessError::Unauthorized("x-api-key header is required!".to_string())
+            })?;
+            let mut keys = vec![(
+                format!("api_key:{}", api_key.id),
+                limiter.config.api_key_limit(api_key.id),
+            )];
+
+            // ID tokenが無効な場合はapi keyのみで制限し、エラーはroute側で返す
+            let req = if req.headers().contains_key(header::AUTHORIZATION) {
+                let (http_req, payload) = req.into_parts();
+                if let Ok(user) = AuthenticatedUser::authenticate(&http_req).await {
+                    let limit = limiter.config.uid_limit(&user.uid);
+                    keys.push((format!("uid:{}", user.uid), limit));
+                }
+                ServiceRequest::from_parts(http_req, payload)
+                    .map_err(|_| DataAccessError::Internal(None))?
+            } else {
+                req
+            };
+
+            let quota = limiter
+                .store
+                .hit(&keys, limiter.config.interval, Instant::now())
+                .map_err(|quota| RateLimitExceeded { quota })?;
+
+            let fut = service.borrow_mut().call(req);
+            let mut res = fut.await?;
+            quota.insert_headers(res.headers_mut());
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::jwt::{self, test_util::bearer};
+    use crate::auth::test_util::authorize_all;
+    use actix_web::{test, web, App};
+
+    #[test]
+    fn hit_test() {
+        let store = RateLimitStore::new();
+        let interval = Duration::from_secs(60);
+        let now = Instant::now();
+        let keys = vec![("a".to_string(), 3), ("b".to_string(), 2)];
+
+        let quota = store.hit(&keys, interval, now).unwrap();
+        assert_eq!(quota.limit, 2);
+        assert_eq!(quota.remaining, 1);
+        assert_eq!(quota.reset, interval);
+
+        let later = now + Duration::from_secs(20);
+        let quota = store.hit(&keys, interval, later).unwrap();
+        assert_eq!(quota.remaining, 0);
+        assert_eq!(quota.reset, Duration::from_secs(40));
+
+        // bが上限に達しているため、aの件数も増やさない
+        let quota = store.hit(&keys, interval, later).unwrap_err();
+        assert_eq!(quota.limit, 2);
+        let quota = store.hit(&keys[..1], interval, later).unwrap();
+        assert_eq!(quota.remaining, 0);
+
+        // windowが切り替わるとリセットされる
+        let quota = store.hit(&keys, interval, now + interval).unwrap();
+        assert_eq!(quota.remaining, 1);
+    }
+
+    #[actix_rt::test]
+    async fn rate_limiter_test() {
+        let mut config = RateLimitConfig {
+            max_requests: 3,
+            ..RateLimitConfig::default()
+        };
+        config.uids.insert("limited".to_string(), 1);
+        let store = RateLimitStore::new();
+
+        let mut app = test::init_service(
+            App::new().app_data(jwt::test_util::verifier()).service(
+                web::scope("")
+                    .wrap(RateLimiter::new(config, store.clone()))
+                    .wrap_fn(authorize_all)
+                    .route("/", web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+
+        let call = |uid: Option<&str>| {
+            let mut req = test::TestRequest::get().uri("/");
+            if let Some(uid) = uid {
+                req = req.header("authorization", bearer(uid));
+            }
+            req.to_request()
+        };
+
+        let resp = test::call_service(&mut app, call(Some("limited"))).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(resp.headers().get(LIMIT_HEADER).unwrap(), "1");
+        assert_eq!(resp.headers().get(REMAINING_HEADER).unwrap(), "0");
+        assert_eq!(resp.headers().get(RESET_HEADER).unwrap(), "60");
+
+        // uid毎の上限
+        let resp = app.call(call(Some("limited"))).await;
+        let resp = match resp {
+            Ok(_) => panic!("must be rejected"),
+            Err(e) => e.as_response_error().error_response(),
+        };
+        assert_eq!(resp.status().as_u16(), 429);
+        assert_eq!(resp.headers().get(LIMIT_HEADER).unwrap(), "1");
+        assert_eq!(resp.headers().get(REMAINING_HEADER).unwrap(), "0");
+        assert!(resp.headers().contains_key("retry-after"));
+        let resp = ServiceResponse::new(test::TestRequest::default().to_http_request(), resp);
+        let body = test::read_body(resp).await;
+        let problem: Problem = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.status, 429);
+
+        // api key毎の上限. 無効なID tokenはapi keyのみで数える
+        let resp = test::call_service(&mut app, call(None)).await;
+        assert_eq!(resp.headers().get(REMAINING_HEADER).unwrap(), "1");
+        let resp = test::TestRequest::get()
+            .uri("/")
+            .header("authorization", "Bearer invalid")
+            .to_request();
+        let resp = test::call_service(&mut app, resp).await;
+        assert_eq!(resp.headers().get(LIMIT_HEADER).unwrap(), "3");
+        assert_eq!(resp.headers().get(REMAINING_HEADER).unwrap(), "0");
+        assert!(app.call(call(Some("other"))).await.is_err());
+    }
+}
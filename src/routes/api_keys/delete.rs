@@ -0,0 +1,18 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::api_keys::ApiKeyDriver;
+use crate::usecase::api_keys::revoke::{self, InputData};
+use actix_web::{web, HttpResponse};
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    item: web::Json<InputData>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.into_inner();
+    let api_key = Repository::new(&pool)
+        .run(move |connection| revoke::execute(ApiKeyDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(api_key))
+}
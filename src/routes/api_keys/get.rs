@@ -0,0 +1,14 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::api_keys::ApiKeyDriver;
+use crate::usecase::api_keys::list;
+use actix_web::{web, HttpResponse};
+
+pub async fn route(pool: web::Data<DbPool>) -> Result<HttpResponse, DataAccessError> {
+    let api_keys = Repository::new(&pool)
+        .run(move |connection| list::execute(ApiKeyDriver::new(connection)))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(api_keys))
+}
@@ -0,0 +1,101 @@
+mod delete;
+mod get;
+mod post;
+mod rotate;
+
+use crate::auth::RequireScope;
+use crate::domain::entity::api_key::Scope;
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/")
+            .wrap(RequireScope::all(Scope::KeysAdmin))
+            .route("", web::get().to(get::route))
+            .route("", web::post().to(post::route))
+            .route("", web::delete().to(delete::route))
+            .route("rotate", web::post().to(rotate::route)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::test_util::authorize_all;
+    use crate::database_utils::pool::test_util::setup_connection_pool;
+    use crate::domain::entity::api_key::{ApiKey, IssuedApiKey};
+    use crate::usecase::api_keys::{issue, revoke, rotate};
+    use actix_web::{test, App};
+
+    /// # scenario
+    ///
+    /// 1. issue
+    /// 2. rotate
+    /// 3. revoke
+    /// 4. list
+    #[actix_rt::test]
+    async fn api_key_scenario() {
+        let pool = setup_connection_pool();
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(&issue::InputData {
+                name: "mobile app".to_string(),
+                scopes: vec!["records:read".to_string(), "records:write".to_string()],
+                expires_at: None,
+            })
+            .to_request();
+        let issued: IssuedApiKey = test::read_response_json(&mut app, req).await;
+        assert_eq!(issued.api_key.name, "mobile app");
+
+        let req = test::TestRequest::post()
+            .uri("/")
+            .set_json(&issue::InputData {
+                name: "mobile app".to_string(),
+                scopes: vec!["everything".to_string()],
+                expires_at: None,
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+
+        let req = test::TestRequest::post()
+            .uri("/rotate")
+            .set_json(&rotate::InputData {
+                id: issued.api_key.id,
+                grace_seconds: None,
+            })
+            .to_request();
+        let rotated: IssuedApiKey = test::read_response_json(&mut app, req).await;
+        assert_ne!(rotated.api_key.id, issued.api_key.id);
+        assert_ne!(rotated.key, issued.key);
+
+        let req = test::TestRequest::delete()
+            .uri("/")
+            .set_json(&revoke::InputData {
+                id: issued.api_key.id,
+            })
+            .to_request();
+        let revoked: ApiKey = test::read_response_json(&mut app, req).await;
+        assert!(revoked.revoked_at.is_some());
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        let body = test::read_body(resp).await;
+        // 鍵そのものとhashは一覧に含めない
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!text.contains(&rotated.key));
+        assert!(!text.contains("key_hash"));
+        let api_keys: Vec<ApiKey> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(api_keys.len(), 2);
+    }
+}
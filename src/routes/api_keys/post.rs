@@ -0,0 +1,19 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::api_keys::ApiKeyDriver;
+use crate::usecase::api_keys::issue::{self, InputData};
+use actix_web::{web, HttpResponse};
+
+/// 鍵そのものはこのレスポンスでのみ返す.
+pub async fn route(
+    pool: web::Data<DbPool>,
+    item: web::Json<InputData>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.into_inner();
+    let issued = Repository::new(&pool)
+        .run(move |connection| issue::execute(ApiKeyDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Created().json(issued))
+}
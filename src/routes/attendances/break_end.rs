@@ -0,0 +1,21 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::metrics::{self, RecordOperation};
+use crate::usecase::attendance_records::break_end::{self, InputData};
+use actix_web::{web, HttpResponse};
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = InputData { uid: user.uid };
+    let record = Repository::new(&pool)
+        .run(move |connection| break_end::execute(AttendanceRecordDriver::new(connection), input))
+        .await?;
+
+    metrics::record_operation(RecordOperation::Updated);
+    Ok(HttpResponse::Ok().json(record))
+}
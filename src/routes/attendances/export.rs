@@ -0,0 +1,123 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::usecase::attendance_records::export::{
+    self, Column, ExportCursor, ExportRow, InputData, BATCH_SIZE, BOM,
+};
+use actix_web::web::Bytes;
+use actix_web::{http::header, web, Error, HttpResponse};
+use futures::stream;
+use serde::{Deserialize, Serialize};
+
+pub const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetParams {
+    /// 全ユーザーの出力でのみ使用する. 省略時は全ユーザー
+    pub uid: Option<String>,
+    pub from: i64,
+    pub to: i64,
+    /// カンマ区切りの列名. 省略時はすべての列
+    pub columns: Option<String>,
+    /// trueの場合、先頭にBOMを付ける(日本語版のExcel向け)
+    pub bom: Option<bool>,
+}
+
+impl GetParams {
+    pub fn to_input_data(&self, uid: Option<String>) -> Result<InputData, DataAccessError> {
+        let columns = match &self.columns {
+            Some(columns) => Column::parse_list(columns)
+                .map_err(|message| DataAccessError::validation("columns", &message))?,
+            None => Column::ALL.to_vec(),
+        };
+
+        Ok(InputData {
+            uid,
+            from: self.from,
+            to: self.to,
+            columns,
+        })
+    }
+}
+
+/// 認証したユーザーの勤怠記録.
+pub async fn route(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    item: web::Query<GetParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(Some(user.uid))?;
+    respond(pool.get_ref().clone(), input, item.bom.unwrap_or(false)).await
+}
+
+/// 全ユーザー、または`uid`で指定したユーザーの勤怠記録.
+pub async fn route_all(
+    pool: web::Data<DbPool>,
+    item: web::Query<GetParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(item.uid.clone())?;
+    respond(pool.get_ref().clone(), input, item.bom.unwrap_or(false)).await
+}
+
+async fn fetch(
+    pool: &DbPool,
+    input: &InputData,
+    after: Option<ExportCursor>,
+) -> Result<Vec<ExportRow>, DataAccessError> {
+    let input = input.clone();
+    Repository::new(pool)
+        .run(move |connection| {
+            export::execute(AttendanceRecordDriver::new(connection), &input, after)
+        })
+        .await
+}
+
+/// 最初の取得で検証と存在確認を行い、エラーは通常の応答として返す.
+/// 以降は`BATCH_SIZE`件ずつ取得しながら送信する.
+async fn respond(
+    pool: DbPool,
+    input: InputData,
+    bom: bool,
+) -> Result<HttpResponse, DataAccessError> {
+    let first = fetch(&pool, &input, None).await?;
+
+    let mut head = if bom { BOM.to_string() } else { String::new() };
+    head.push_str(&export::header(&input.columns));
+    let head = stream::once(async move { Ok::<Bytes, Error>(Bytes::from(head)) });
+
+    let rows = stream::unfold(Some(Ok(first)), move |state| {
+        let pool = pool.clone();
+        let input = input.clone();
+        async move {
+            let rows = match state? {
+                Ok(rows) => rows,
+                // 送信を中断し、不完全なファイルであることをクライアントに伝える
+                Err(e) => {
+                    log::error!("failed to export attendance records: {}", e);
+                    return Some((Err(Error::from(e)), None));
+                }
+            };
+            let chunk = rows
+                .iter()
+                .map(|row| row.to_csv(&input.columns))
+                .collect::<String>();
+            let next = match rows.last() {
+                Some(last) if rows.len() as i64 >= BATCH_SIZE => {
+                    Some(fetch(&pool, &input, Some(ExportCursor::of(last))).await)
+                }
+                _ => None,
+            };
+            Some((Ok(Bytes::from(chunk)), next))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(CONTENT_TYPE)
+        .header(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"attendance_records.csv\"",
+        )
+        .streaming(Box::pin(stream::StreamExt::chain(head, rows))))
+}
@@ -1,37 +1,99 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
 use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::domain::entity::record_page::RecordPage;
 use crate::driver::attendance_records::AttendanceRecordDriver;
-use crate::usecase::attendance_records::search_by_user::{self, InputData};
-use actix_web::{web, HttpResponse};
+use crate::usecase::attendance_records::search_by_user::{self, InputData, SortKey, SortOrder};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetParams {
-    pub uid: String,
     pub page: Option<i32>,
     pub count: Option<i32>,
+    pub cursor: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub sort: Option<SortKey>,
+    pub order: Option<SortOrder>,
+    /// trueの場合、退勤前のレコードのみ
+    pub open: Option<bool>,
+    /// trueの場合、備考が入力されたレコードのみ
+    pub has_note: Option<bool>,
+    /// trueの場合、条件に一致するレコードの総数を返す
+    pub total: Option<bool>,
 }
 
 impl GetParams {
-    pub fn to_input_data(&self) -> InputData {
+    pub fn to_input_data(&self, uid: String) -> InputData {
         let page = self.page.unwrap_or_else(|| 1);
         let count = self.count.unwrap_or_else(|| 10);
 
         InputData {
-            uid: self.uid.clone(),
+            uid,
             page,
             count,
+            cursor: self.cursor.clone(),
+            from: self.from,
+            to: self.to,
+            sort: self.sort,
+            order: self.order,
+            open_only: self.open.unwrap_or(false),
+            has_note: self.has_note.unwrap_or(false),
+            with_total: self.total.unwrap_or(false),
         }
     }
-}
 
-pub async fn route(pool: web::Data<DbPool>, item: web::Query<GetParams>) -> HttpResponse {
-    let connection = pool
-        .get()
-        .expect("couldn't get driver connection from pool");
-    let attendance_driver = AttendanceRecordDriver::new(&connection);
+    /// RFC 8288のLinkヘッダ. 次のページはカーソルがあればカーソル、なければページ番号で示す.
+    fn links(&self, path: &str, result: &RecordPage) -> String {
+        let link = |params: &GetParams, rel: &str| {
+            format!(
+                "<{}?{}>; rel=\"{}\"",
+                path,
+                serde_urlencoded::to_string(params).unwrap_or_default(),
+                rel
+            )
+        };
+        let first = GetParams {
+            page: None,
+            cursor: None,
+            ..self.clone()
+        };
+        let mut links = vec![link(&first, "first")];
+
+        if result.has_more {
+            let next = match &result.next_cursor {
+                Some(cursor) => GetParams {
+                    cursor: Some(cursor.clone()),
+                    ..first
+                },
+                None => GetParams {
+                    page: Some(self.page.unwrap_or(1) + 1),
+                    ..first
+                },
+            };
+            links.push(link(&next, "next"));
+        }
 
-    match search_by_user::execute(attendance_driver, item.to_input_data()) {
-        Ok(records) => HttpResponse::Ok().json(records),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+        links.join(", ")
     }
 }
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    item: web::Query<GetParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid);
+    let result = Repository::new(&pool)
+        .run(move |connection| {
+            search_by_user::execute(AttendanceRecordDriver::new(connection), input)
+        })
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .header(header::LINK, item.links(req.path(), &result))
+        .json(result))
+}
@@ -0,0 +1,51 @@
+use crate::config::Settings;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::usecase::attendance_records::import::{self, InputData, Mode};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+/// 本文の大きさの上限(byte).
+pub const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostParams {
+    /// 組み込みの形式、または設定ファイルの`import.mappings`の名前. 省略時はcsv
+    pub format: Option<String>,
+    /// 省略時はall_or_nothing
+    pub mode: Option<Mode>,
+}
+
+/// 取り込んだ場合は200、すべて取り消した場合は422で、どちらも行毎の結果を返す.
+pub async fn route(
+    pool: web::Data<DbPool>,
+    settings: web::Data<Settings>,
+    params: web::Query<PostParams>,
+    body: String,
+) -> Result<HttpResponse, DataAccessError> {
+    let importer = import::importer(
+        params.format.as_deref().unwrap_or("csv"),
+        &settings.import.mappings,
+    )?;
+    let input = InputData {
+        mode: params.mode.unwrap_or(Mode::AllOrNothing),
+        body,
+    };
+    let report = Repository::new(&pool)
+        .run(move |connection| {
+            import::execute(
+                AttendanceRecordDriver::new(connection),
+                importer.as_ref(),
+                input,
+            )
+        })
+        .await?;
+
+    if report.committed {
+        Ok(HttpResponse::Ok().json(report))
+    } else {
+        Ok(HttpResponse::UnprocessableEntity().json(report))
+    }
+}
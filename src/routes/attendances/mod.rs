@@ -1,17 +1,45 @@
+mod break_end;
+mod break_start;
+mod clock_in;
+mod clock_out;
 mod delete;
+mod export;
 mod get;
+mod import;
 mod patch;
 mod post_record;
+mod summary;
 
+use crate::auth::RequireScope;
+use crate::domain::entity::api_key::Scope;
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
+    // 全ユーザーの出力と一括登録は管理者のみ. "/"のscopeより先に登録する
+    cfg.service(
+        web::resource("/export_all.csv")
+            .wrap(RequireScope::all(Scope::UsersAdmin))
+            .route(web::get().to(export::route_all)),
+    );
+    cfg.service(
+        web::resource("/import")
+            .wrap(RequireScope::all(Scope::UsersAdmin))
+            .app_data(web::PayloadConfig::new(import::MAX_BODY_BYTES))
+            .route(web::post().to(import::route)),
+    );
     cfg.service(
         web::scope("/")
+            .wrap(RequireScope::new(Scope::RecordsRead, Scope::RecordsWrite))
             .route("", web::get().to(get::route))
             .route("", web::post().to(post_record::route))
             .route("", web::delete().to(delete::route))
-            .route("", web::patch().to(patch::route)),
+            .route("", web::patch().to(patch::route))
+            .route("clock_in", web::post().to(clock_in::route))
+            .route("clock_out", web::post().to(clock_out::route))
+            .route("break_start", web::post().to(break_start::route))
+            .route("break_end", web::post().to(break_end::route))
+            .route("summary", web::get().to(summary::route))
+            .route("export.csv", web::get().to(export::route)),
     );
 }
 
@@ -19,11 +47,22 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::jwt::{self, test_util::bearer};
+    use crate::auth::test_util::authorize_all;
+    use crate::config;
     use crate::database_utils::pool::test_util::setup_connection_pool;
     use crate::domain::entity::attendance_record::AttendanceRecord;
+    use crate::domain::entity::record_page::RecordPage;
+    use crate::domain::entity::work_summary::WorkSummary;
     use crate::driver::users::test_utils::test_user_by_pool;
+    use crate::driver::users::UserDriver;
+    use crate::routes::problem::{self, Problem};
+    use crate::usecase::attendance_records::add::BreakInput;
+    use crate::usecase::attendance_records::import::ImportReport;
+    use crate::usecase::users::add::CreateUserUseCase;
     use actix_web::{test, web, App};
-    use chrono::{Duration, Local, NaiveDateTime};
+    use chrono::{Duration, Local, TimeZone, Utc};
+    use chrono_tz::Asia::Tokyo;
 
     #[actix_rt::test]
     async fn attendance_get_post_status_test() {
@@ -32,6 +71,8 @@ mod tests {
         let mut app = test::init_service(
             App::new()
                 .data(pool.clone())
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
                 .service(web::scope("").configure(config)),
         )
         .await;
@@ -39,32 +80,142 @@ mod tests {
         let test_user = test_user_by_pool(pool.clone());
 
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_user.uid))
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 200);
 
         let end_time_date = Local::now();
         let start_time_date = end_time_date - Duration::hours(8);
-        let break_time = 60 * 60 * 1000;
+        let break_start_date = start_time_date + Duration::hours(3);
 
         let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
             .uri("/")
             .set_json(&post_record::PostParams {
-                uid: test_user.uid.clone(),
                 start_time: start_time_date.timestamp(),
                 end_time: end_time_date.timestamp(),
-                break_time,
+                breaks: vec![BreakInput {
+                    start_time: break_start_date.timestamp(),
+                    end_time: (break_start_date + Duration::hours(1)).timestamp(),
+                }],
+                note: None,
             }) // int max
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 201);
 
+        // 不正な入力は項目毎のエラーを返す
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
+            .set_json(&post_record::PostParams {
+                start_time: end_time_date.timestamp(),
+                end_time: start_time_date.timestamp(),
+                breaks: vec![BreakInput {
+                    start_time: (end_time_date + Duration::days(2)).timestamp(),
+                    end_time: (end_time_date + Duration::days(2)).timestamp(),
+                }],
+                note: None,
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            problem::CONTENT_TYPE
+        );
+        let resp_problem: Problem = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(resp_problem.status, 422);
+        let fields: Vec<&str> = resp_problem
+            .errors
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "end_time",
+                "breaks[0].start_time",
+                "breaks[0].end_time",
+                "breaks[0].end_time"
+            ]
+        );
+
+        // 勤務時間が重複するレコード
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
+            .set_json(&post_record::PostParams {
+                start_time: (start_time_date + Duration::hours(1)).timestamp(),
+                end_time: (end_time_date + Duration::hours(1)).timestamp(),
+                breaks: vec![],
+                note: None,
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 409);
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!(
+                "/?from={}&sort=start_time&order=asc&has_note=true",
+                start_time_date.timestamp()
+            ))
+            .to_request();
+        let resp: RecordPage = test::read_response_json(&mut app, req).await;
+        assert!(resp.records.is_empty());
+        assert!(!resp.has_more);
+
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_user.uid))
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/?count=1&page=1&total=true")
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 200);
+        let link = resp.headers().get("link").unwrap().to_str().unwrap();
+        assert!(link.starts_with("</?count=1&total=true>; rel=\"first\""));
+
+        // 不正なページ指定と、日時に変換できない期間
+        for query in &[
+            "page=0",
+            "count=0",
+            "count=101",
+            "cursor=invalid",
+            "from=99999999999999999",
+            "to=-99999999999999999",
+        ] {
+            let req = test::TestRequest::get()
+                .header("authorization", bearer(&test_user.uid))
+                .uri(&format!("/?{}", query))
+                .to_request();
+            let resp = test::call_service(&mut app, req).await;
+            assert_eq!(resp.status().as_u16(), 422);
+        }
+
+        // offsetがi32に収まらないページ
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/?count=100&page=2147483647")
+            .to_request();
+        let resp: RecordPage = test::read_response_json(&mut app, req).await;
+        assert!(resp.records.is_empty());
+        assert!(!resp.has_more);
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/?sort=break_time")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
     }
 
     #[actix_rt::test]
@@ -74,6 +225,8 @@ mod tests {
         let mut app = test::init_service(
             App::new()
                 .data(pool.clone())
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
                 .service(web::scope("").configure(config)),
         )
         .await;
@@ -82,76 +235,411 @@ mod tests {
 
         let end_time_date = Local::now();
         let start_time_date = end_time_date - Duration::hours(8);
-        let break_time = 60 * 60 * 1000;
+        let break_start_date = start_time_date + Duration::hours(3);
 
         let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
             .uri("/")
             .set_json(&post_record::PostParams {
-                uid: test_user.uid.clone(),
                 start_time: start_time_date.timestamp(),
                 end_time: end_time_date.timestamp(),
-                break_time,
+                breaks: vec![BreakInput {
+                    start_time: break_start_date.timestamp(),
+                    end_time: (break_start_date + Duration::hours(1)).timestamp(),
+                }],
+                note: None,
             }) // int max
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 201);
 
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_user.uid))
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
             .to_request();
-        let resp_records: Vec<AttendanceRecord> = test::read_response_json(&mut app, req).await;
+        let resp_page: RecordPage = test::read_response_json(&mut app, req).await;
+        let resp_records = resp_page.records;
         assert!(!resp_records.is_empty());
         let resp_record = resp_records.first().unwrap();
 
         // update
         let end_time2_date = Local::now();
         let start_time2_date = end_time_date - Duration::hours(8);
-        let break_time2 = 60 * 60 * 1000;
+        let break_start2_date = start_time2_date + Duration::hours(4);
 
         let req = test::TestRequest::patch()
+            .header("authorization", bearer(&test_user.uid))
             .uri("/")
             .set_json(&patch::JsonBody {
-                uid: test_user.uid.clone(),
                 id: resp_record.id,
                 start_time: Some(start_time2_date.timestamp()),
                 end_time: Some(end_time2_date.timestamp()),
-                break_time: Some(break_time2),
+                breaks: Some(vec![BreakInput {
+                    start_time: break_start2_date.timestamp(),
+                    end_time: (break_start2_date + Duration::minutes(45)).timestamp(),
+                }]),
+                note: None,
             })
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 204);
 
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_user.uid))
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
             .to_request();
-        let resp_records: Vec<AttendanceRecord> = test::read_response_json(&mut app, req).await;
+        let resp_page: RecordPage = test::read_response_json(&mut app, req).await;
+        let resp_records = resp_page.records;
         assert!(!resp_records.is_empty());
         let resp_record = resp_records.first().unwrap();
         assert_eq!(
             resp_record.start_time,
-            NaiveDateTime::from_timestamp(start_time2_date.timestamp(), 0)
+            Utc.timestamp(start_time2_date.timestamp(), 0)
         );
         assert_eq!(
             resp_record.end_time,
-            NaiveDateTime::from_timestamp(end_time2_date.timestamp(), 0)
+            Some(Utc.timestamp(end_time2_date.timestamp(), 0))
+        );
+        assert_eq!(resp_record.breaks.len(), 1);
+        assert_eq!(resp_record.break_seconds, 45 * 60);
+        let working_time = resp_record.working_time.unwrap();
+        assert_eq!(
+            working_time.worked_minutes,
+            (end_time2_date - start_time2_date).num_minutes() - 45
         );
-        assert_eq!(resp_record.break_time, break_time2);
+
+        // 他のユーザーのレコードは削除できない
+        let other_user = {
+            let connection = pool.get().unwrap();
+            UserDriver::new(&connection)
+                .create("other user".to_string(), None)
+                .unwrap()
+        };
+        let req = test::TestRequest::delete()
+            .header("authorization", bearer(&other_user.uid))
+            .uri("/")
+            .set_json(&delete::JsonBody { id: resp_record.id })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 403);
 
         // delete
         let req = test::TestRequest::delete()
+            .header("authorization", bearer(&test_user.uid))
             .uri("/")
-            .set_json(&delete::JsonBody {
-                uid: test_user.uid.clone(),
-                id: resp_record.id,
-            })
+            .set_json(&delete::JsonBody { id: resp_record.id })
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 204);
 
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_user.uid))
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
             .to_request();
-        let resp_records: Vec<AttendanceRecord> = test::read_response_json(&mut app, req).await;
+        let resp_page: RecordPage = test::read_response_json(&mut app, req).await;
+        let resp_records = resp_page.records;
         assert!(resp_records.is_empty());
     }
+
+    #[actix_rt::test]
+    async fn clock_in_out_test() {
+        let pool = setup_connection_pool();
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let test_user = test_user_by_pool(pool.clone());
+
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/clock_in")
+            .to_request();
+        let resp_record: AttendanceRecord = test::read_response_json(&mut app, req).await;
+        assert!(resp_record.is_open());
+
+        // 2重の出勤打刻
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/clock_in")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 409);
+        let resp_problem: Problem = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(resp_problem.detail, Some("Already clocked in!".to_string()));
+
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/break_start")
+            .to_request();
+        let resp_record: AttendanceRecord = test::read_response_json(&mut app, req).await;
+        assert!(resp_record.is_on_break());
+
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/break_end")
+            .to_request();
+        let resp_record: AttendanceRecord = test::read_response_json(&mut app, req).await;
+        assert!(!resp_record.is_on_break());
+        assert_eq!(resp_record.breaks.len(), 1);
+
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/clock_out")
+            .to_request();
+        let resp_clocked_out: AttendanceRecord = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp_clocked_out.id, resp_record.id);
+        assert!(!resp_clocked_out.is_open());
+
+        // 出勤していない状態での退勤打刻
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/clock_out")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 409);
+
+        // 未登録のユーザー
+        let req = test::TestRequest::post()
+            .header("authorization", bearer("unknown user"))
+            .uri("/clock_in")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 404);
+    }
+
+    #[actix_rt::test]
+    async fn summary_test() {
+        let pool = setup_connection_pool();
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let test_user = test_user_by_pool(pool.clone());
+
+        let end_time_date = Local::now();
+        let start_time_date = end_time_date - Duration::hours(8);
+
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
+            .set_json(&post_record::PostParams {
+                start_time: start_time_date.timestamp(),
+                end_time: end_time_date.timestamp(),
+                breaks: vec![],
+                note: None,
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 201);
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!(
+                "/summary?from={}&to={}&group_by=month",
+                (start_time_date - Duration::days(40)).timestamp(),
+                end_time_date.timestamp()
+            ))
+            .to_request();
+        let resp_summary: WorkSummary = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp_summary.total.record_count, 1);
+        assert_eq!(resp_summary.total.worked_minutes, 8 * 60);
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/summary?from=0&to=1&group_by=year")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 400);
+
+        // 日時に変換できない期間
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/summary?from=0&to=99999999999999999")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+
+    #[actix_rt::test]
+    async fn export_test() {
+        let pool = setup_connection_pool();
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let test_user = test_user_by_pool(pool.clone());
+        let start_time = Tokyo.ymd(2020, 9, 1).and_hms(9, 0, 0);
+        let req = test::TestRequest::post()
+            .header("authorization", bearer(&test_user.uid))
+            .uri("/")
+            .set_json(&post_record::PostParams {
+                start_time: start_time.timestamp(),
+                end_time: (start_time + Duration::hours(9)).timestamp(),
+                breaks: vec![],
+                note: Some("早退, 再出勤".to_string()),
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 201);
+
+        let range = format!(
+            "from={}&to={}",
+            start_time.timestamp(),
+            (start_time + Duration::days(1)).timestamp()
+        );
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!(
+                "/export.csv?{}&columns=date,start_time,worked_minutes,overtime_minutes,note&bom=true",
+                range
+            ))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            export::CONTENT_TYPE
+        );
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(
+            body,
+            "\u{feff}date,start_time,worked_minutes,overtime_minutes,note\r\n\
+             2020-09-01,2020-09-01 09:00:00,540,60,\"早退, 再出勤\"\r\n"
+        );
+
+        // 管理者向けの全ユーザーの出力
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/export_all.csv?{}&uid={}&columns=uid,date",
+                range, test_user.uid
+            ))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        let body = test::read_body(resp).await;
+        assert_eq!(
+            body,
+            format!("uid,date\r\n{},2020-09-01\r\n", test_user.uid)
+        );
+
+        for (uri, status) in &[
+            (format!("/export.csv?{}&columns=uid,salary", range), 422),
+            ("/export.csv?from=1&to=0".to_string(), 422),
+            (format!("/export_all.csv?{}&uid=unknown", range), 404),
+        ] {
+            let req = test::TestRequest::get()
+                .header("authorization", bearer(&test_user.uid))
+                .uri(uri)
+                .to_request();
+            let resp = test::call_service(&mut app, req).await;
+            assert_eq!(resp.status().as_u16(), *status, "{}", uri);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn import_test() {
+        let pool = setup_connection_pool();
+        let mut settings = config::test_util::settings();
+        settings.import.mappings.insert(
+            "kintai".to_string(),
+            toml::from_str(
+                "uid = \"社員番号\"\ndate = \"日付\"\nstart_time = \"出勤\"\nend_time = \"退勤\"",
+            )
+            .unwrap(),
+        );
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .data(settings)
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let test_user = test_user_by_pool(pool.clone());
+        let start_time = Tokyo.ymd(2020, 7, 1).and_hms(9, 0, 0);
+        let line = |day: i64| {
+            format!(
+                "{{\"uid\": \"{}\", \"start_time\": {}, \"end_time\": {}}}\n",
+                test_user.uid,
+                (start_time + Duration::days(day)).timestamp(),
+                (start_time + Duration::days(day) + Duration::hours(8)).timestamp()
+            )
+        };
+        let body = format!("{}{}not json\n", line(0), line(1));
+
+        // 3行目が不正なため、すべて取り消す
+        let req = test::TestRequest::post()
+            .uri("/import?format=json_lines")
+            .set_payload(body.clone())
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+        let report: ImportReport = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert!(!report.committed);
+        assert_eq!(report.rows[2].line, 3);
+        assert_eq!(report.rows[2].errors[0].field, "line");
+
+        let req = test::TestRequest::post()
+            .uri("/import?format=json_lines&mode=skip_invalid")
+            .set_payload(body)
+            .to_request();
+        let report: ImportReport = test::read_response_json(&mut app, req).await;
+        assert!(report.committed);
+        assert_eq!((report.imported, report.failed), (2, 1));
+
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!("/?from={}&total=true", start_time.timestamp()))
+            .to_request();
+        let resp: RecordPage = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp.total, Some(2));
+
+        // 設定ファイルで定義した形式
+        let req = test::TestRequest::post()
+            .uri("/import?format=kintai")
+            .set_payload(format!(
+                "社員番号,日付,出勤,退勤\n{},2020/07/03,22:00,06:00\n",
+                test_user.uid
+            ))
+            .to_request();
+        let report: ImportReport = test::read_response_json(&mut app, req).await;
+        assert_eq!(report.imported, 1);
+
+        // 既定の上限(256KiB)より大きい本文も受け付ける
+        let body = format!("{}{}\n", line(5), " ".repeat(512 * 1024));
+        let req = test::TestRequest::post()
+            .uri("/import?format=json_lines")
+            .set_payload(body)
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+
+        let req = test::TestRequest::post()
+            .uri("/import?format=xml")
+            .set_payload("")
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
 }
@@ -1,38 +1,46 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
 use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
 use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::metrics::{self, RecordOperation};
+use crate::usecase::attendance_records::add::BreakInput;
 use crate::usecase::attendance_records::update::{self, InputData};
 use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonBody {
-    pub uid: String,
     pub id: i32,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
-    pub break_time: Option<i32>,
+    pub breaks: Option<Vec<BreakInput>>,
+    pub note: Option<String>,
 }
 
 impl JsonBody {
-    pub fn to_input_data(&self) -> InputData {
+    pub fn to_input_data(&self, uid: String) -> InputData {
         InputData {
-            uid: self.uid.clone(),
+            uid,
             id: self.id,
             start_time: self.start_time,
             end_time: self.end_time,
-            break_time: self.break_time,
+            breaks: self.breaks.clone(),
+            note: self.note.clone(),
         }
     }
 }
 
-pub async fn route(pool: web::Data<DbPool>, item: web::Json<JsonBody>) -> HttpResponse {
-    let connection = pool
-        .get()
-        .expect("couldn't get driver connection from pool");
-    let attendance_driver = AttendanceRecordDriver::new(&connection);
+pub async fn route(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    item: web::Json<JsonBody>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid);
+    Repository::new(&pool)
+        .run(move |connection| update::execute(AttendanceRecordDriver::new(connection), input))
+        .await?;
 
-    match update::execute(attendance_driver, item.to_input_data()) {
-        Ok(_) => HttpResponse::NoContent().finish(),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    metrics::record_operation(RecordOperation::Updated);
+    Ok(HttpResponse::NoContent().finish())
 }
@@ -1,36 +1,44 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
 use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
 use crate::driver::attendance_records::AttendanceRecordDriver;
-use crate::usecase::attendance_records::add::{self, InputData};
+use crate::metrics::{self, RecordOperation};
+use crate::usecase::attendance_records::add::{self, BreakInput, InputData};
 use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostParams {
-    pub uid: String,
     pub start_time: i64,
     pub end_time: i64,
-    pub break_time: i32,
+    #[serde(default)]
+    pub breaks: Vec<BreakInput>,
+    pub note: Option<String>,
 }
 
 impl PostParams {
-    pub fn to_input_data(&self) -> InputData {
+    pub fn to_input_data(&self, uid: String) -> InputData {
         InputData {
-            uid: self.uid.clone(),
+            uid,
             start_time: self.start_time,
             end_time: self.end_time,
-            break_time: self.break_time,
+            breaks: self.breaks.clone(),
+            note: self.note.clone(),
         }
     }
 }
 
-pub async fn route(pool: web::Data<DbPool>, item: web::Json<PostParams>) -> HttpResponse {
-    let connection = pool
-        .get()
-        .expect("couldn't get driver connection from pool");
-    let attendance_driver = AttendanceRecordDriver::new(&connection);
+pub async fn route(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    item: web::Json<PostParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid);
+    let category = Repository::new(&pool)
+        .run(move |connection| add::execute(AttendanceRecordDriver::new(connection), input))
+        .await?;
 
-    match add::execute(attendance_driver, item.to_input_data()) {
-        Ok(category) => HttpResponse::Created().json(category),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    metrics::record_operation(RecordOperation::Created);
+    Ok(HttpResponse::Created().json(category))
 }
@@ -0,0 +1,39 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::attendance_records::AttendanceRecordDriver;
+use crate::usecase::attendance_records::summary::{self, GroupBy, InputData};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetParams {
+    pub from: i64,
+    pub to: i64,
+    pub group_by: Option<GroupBy>,
+}
+
+impl GetParams {
+    pub fn to_input_data(&self, uid: String) -> InputData {
+        InputData {
+            uid,
+            from: self.from,
+            to: self.to,
+            group_by: self.group_by.unwrap_or(GroupBy::Day),
+        }
+    }
+}
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    item: web::Query<GetParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid);
+    let summary = Repository::new(&pool)
+        .run(move |connection| summary::execute(AttendanceRecordDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(summary))
+}
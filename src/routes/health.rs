@@ -0,0 +1,187 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::migrations;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use actix_web::{web, HttpResponse};
+use diesel::RunQueryDsl;
+use diesel_migrations::RunMigrationsError;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/healthz", web::get().to(healthz))
+        .route("/readyz", web::get().to(readyz));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Fail,
+}
+
+/// 個々のチェックの結果.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    fn from_result<T>(name: &str, started: Instant, result: Result<T, String>) -> Check {
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        let (status, detail) = match result {
+            Ok(_) => (Status::Ok, None),
+            Err(detail) => (Status::Fail, Some(detail)),
+        };
+        Check {
+            name: name.to_string(),
+            status,
+            latency_ms,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Health {
+    pub status: Status,
+    pub checks: Vec<Check>,
+}
+
+impl Health {
+    fn new(checks: Vec<Check>) -> Health {
+        let status = if checks.iter().all(|check| check.status == Status::Ok) {
+            Status::Ok
+        } else {
+            Status::Fail
+        };
+        Health { status, checks }
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        match self.status {
+            Status::Ok => HttpResponse::Ok().json(self),
+            Status::Fail => HttpResponse::ServiceUnavailable().json(self),
+        }
+    }
+}
+
+/// 認証なしで公開するため、接続先などを含むエラーの詳細はログにのみ出力する.
+fn failure(error: DataAccessError) -> String {
+    log::warn!("readiness check failed: {}", error);
+    match error {
+        DataAccessError::Unavailable(_) => "Database is unavailable!".to_string(),
+        _ => "Query failed!".to_string(),
+    }
+}
+
+/// プロセスが応答できるか. データベースには接続しない.
+pub async fn healthz() -> HttpResponse {
+    Health::new(Vec::new()).to_response()
+}
+
+/// データベースに接続でき、未適用のmigrationが無いか.
+pub async fn readyz(pool: web::Data<DbPool>) -> HttpResponse {
+    let repository = Repository::new(&pool);
+
+    let started = Instant::now();
+    let result = repository
+        .run(|connection| {
+            diesel::sql_query("SELECT 1")
+                .execute(connection)
+                .map_err(DataAccessError::from)
+        })
+        .await
+        .map_err(failure);
+    let database = Check::from_result("database", started, result);
+
+    // データベースに接続できない場合はmigrationを確認できない
+    let started = Instant::now();
+    let result = match database.status {
+        Status::Ok => repository
+            .run(|connection| {
+                migrations::pending_versions(connection).map_err(|error| match error {
+                    RunMigrationsError::QueryError(error) => DataAccessError::from(error),
+                    // migrationsディレクトリを読み込めない
+                    error => {
+                        log::error!("failed to read migrations: {}", error);
+                        DataAccessError::Internal(None)
+                    }
+                })
+            })
+            .await
+            .map_err(failure)
+            .and_then(|pending| {
+                if pending.is_empty() {
+                    Ok(())
+                } else {
+                    Err(format!("Pending migrations: {}", pending.join(", ")))
+                }
+            }),
+        Status::Fail => Err("Database is unavailable!".to_string()),
+    };
+    let migrations = Check::from_result("migrations", started, result);
+
+    Health::new(vec![database, migrations]).to_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util::setup_connection_pool;
+    use crate::database_utils::pool::{build_pool, PoolConfig};
+    use actix_web::{test, App};
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn healthz_test() {
+        let mut app = test::init_service(App::new().configure(config)).await;
+
+        let req = test::TestRequest::get().uri("/healthz").to_request();
+        let resp: Health = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp.status, Status::Ok);
+        assert!(resp.checks.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn readyz_test() {
+        let pool = setup_connection_pool();
+        let mut app = test::init_service(App::new().data(pool).configure(config)).await;
+
+        let req = test::TestRequest::get().uri("/readyz").to_request();
+        let resp: Health = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp.status, Status::Ok);
+        let names = resp
+            .checks
+            .iter()
+            .map(|check| check.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["database", "migrations"]);
+    }
+
+    #[actix_rt::test]
+    async fn readyz_unavailable_test() {
+        // 接続できないデータベース
+        let pool = build_pool(
+            "postgres://localhost:1/none",
+            &PoolConfig {
+                max_size: 1,
+                min_idle: Some(0),
+                connection_timeout: Duration::from_millis(200),
+            },
+        );
+        let mut app = test::init_service(App::new().data(pool).configure(config)).await;
+
+        let req = test::TestRequest::get().uri("/readyz").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 503);
+
+        let resp: Health = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(resp.status, Status::Fail);
+        assert!(resp.checks.iter().all(|check| check.status == Status::Fail));
+    }
+}
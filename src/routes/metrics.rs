@@ -0,0 +1,49 @@
+use crate::database_utils::pool::DbPool;
+use crate::metrics;
+use actix_web::{web, HttpResponse};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(index));
+}
+
+pub async fn index(pool: web::Data<DbPool>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(prometheus::TEXT_FORMAT)
+        .body(metrics::render(&pool))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database_utils::pool::test_util::setup_connection_pool;
+    use crate::metrics::RequestMetrics;
+    use crate::routes::health;
+    use actix_web::{test, App};
+
+    #[actix_rt::test]
+    async fn metrics_test() {
+        let pool = setup_connection_pool();
+        let mut app = test::init_service(
+            App::new()
+                .data(pool)
+                .wrap(RequestMetrics)
+                .configure(health::config)
+                .configure(config),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/healthz").to_request();
+        test::call_service(&mut app, req).await;
+        let req = test::TestRequest::get().uri("/no_such_route").to_request();
+        test::call_service(&mut app, req).await;
+
+        let req = test::TestRequest::get().uri("/metrics").to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 200);
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains(r#"http_requests_total{method="GET",route="/healthz",status="200"}"#));
+        assert!(body.contains(r#"route="unmatched",status="404""#));
+        assert!(body.contains("db_pool_connections 1"));
+        assert!(body.contains("http_request_duration_seconds_bucket"));
+    }
+}
@@ -1,2 +1,15 @@
+pub mod api_keys;
 pub mod attendances;
+pub mod health;
+pub mod metrics;
+pub mod payroll;
+pub mod problem;
 pub mod users;
+
+use crate::config::ServerSettings;
+use actix_web::web;
+
+/// JSONのbodyの設定. `App::app_data`で登録する(`App::data`では`Json`から参照されない).
+pub fn json_config(settings: &ServerSettings) -> web::JsonConfig {
+    web::JsonConfig::default().limit(settings.json_limit)
+}
@@ -0,0 +1,41 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::config::Settings;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::payroll::PayrollDriver;
+use crate::usecase::payroll::calculate::{self, InputData};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetParams {
+    pub from: i64,
+    pub to: i64,
+}
+
+impl GetParams {
+    /// 割増率は呼び出し側に指定させず、設定の値を使う.
+    pub fn to_input_data(&self, uid: String, settings: &Settings) -> InputData {
+        InputData {
+            uid,
+            from: self.from,
+            to: self.to,
+            rates: settings.payroll.rates.clone(),
+        }
+    }
+}
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    settings: web::Data<Settings>,
+    user: AuthenticatedUser,
+    item: web::Query<GetParams>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid, &settings);
+    let payroll = Repository::new(&pool)
+        .run(move |connection| calculate::execute(PayrollDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(payroll))
+}
@@ -0,0 +1,104 @@
+mod get;
+mod post_wage;
+
+use crate::auth::RequireScope;
+use crate::domain::entity::api_key::Scope;
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/")
+            .wrap(RequireScope::new(Scope::RecordsRead, Scope::UsersAdmin))
+            .route("", web::get().to(get::route))
+            .route("wages", web::post().to(post_wage::route)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt::{self, test_util::bearer};
+    use crate::auth::test_util::authorize_all;
+    use crate::config::test_util::settings;
+    use crate::database_utils::pool::test_util::setup_connection_pool;
+    use crate::domain::entity::hourly_wage::HourlyWage;
+    use crate::domain::entity::payroll::{Payroll, PremiumRates};
+    use crate::driver::attendance_records::AttendanceRecordDriver;
+    use crate::driver::users::test_utils::test_user_by_pool;
+    use crate::usecase::attendance_records::add::{self, AddRecordUseCase};
+    use actix_web::{test, web, App};
+    use chrono::{NaiveDate, TimeZone};
+    use chrono_tz::Asia::Tokyo;
+
+    #[actix_rt::test]
+    async fn payroll_test() {
+        let pool = setup_connection_pool();
+        let mut settings = settings();
+        settings.payroll.rates = PremiumRates {
+            overtime: 30,
+            ..PremiumRates::default()
+        };
+
+        let mut app = test::init_service(
+            App::new()
+                .data(pool.clone())
+                .data(settings)
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        let test_user = test_user_by_pool(pool.clone());
+        let start_time = Tokyo.ymd(2020, 10, 5).and_hms(9, 0, 0);
+        {
+            let connection = pool.get().unwrap();
+            AttendanceRecordDriver::new(&connection)
+                .add_record(add::InputData {
+                    uid: test_user.uid.clone(),
+                    start_time: start_time.timestamp(),
+                    end_time: start_time.timestamp() + 9 * 60 * 60,
+                    breaks: vec![],
+                    note: None,
+                })
+                .unwrap();
+        }
+
+        let req = test::TestRequest::post()
+            .uri("/wages")
+            .set_json(&post_wage::JsonBody {
+                uid: test_user.uid.clone(),
+                amount: 1000,
+                effective_from: NaiveDate::from_ymd(2020, 10, 1),
+            })
+            .to_request();
+        let resp_wage: HourlyWage = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp_wage.amount, 1000);
+
+        let from = Tokyo.ymd(2020, 10, 1).and_hms(0, 0, 0).timestamp();
+        let to = Tokyo.ymd(2020, 11, 1).and_hms(0, 0, 0).timestamp();
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!("/?from={}&to={}", from, to))
+            .to_request();
+        let resp_payroll: Payroll = test::read_response_json(&mut app, req).await;
+        // 9時間 × 1000円 + 1時間 × 1000円 × 30%(設定の割増率)
+        assert_eq!(resp_payroll.gross_amount, 9000 + 300);
+
+        // 呼び出し側は割増率を指定できない
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!("/?from={}&to={}&overtime_rate=1000", from, to))
+            .to_request();
+        let resp_payroll: Payroll = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp_payroll.gross_amount, 9000 + 300);
+
+        // 日時に変換できない期間
+        let req = test::TestRequest::get()
+            .header("authorization", bearer(&test_user.uid))
+            .uri(&format!("/?from={}&to=99999999999999999", from))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+}
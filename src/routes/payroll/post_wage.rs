@@ -0,0 +1,40 @@
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::payroll::PayrollDriver;
+use crate::logging;
+use crate::usecase::payroll::set_wage::{self, InputData};
+use actix_web::{web, HttpResponse};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonBody {
+    pub uid: String,
+    pub amount: i32,
+    /// yyyy-mm-dd
+    pub effective_from: NaiveDate,
+}
+
+impl JsonBody {
+    pub fn to_input_data(&self) -> InputData {
+        InputData {
+            uid: self.uid.clone(),
+            amount: self.amount,
+            effective_from: self.effective_from,
+        }
+    }
+}
+
+pub async fn route(
+    pool: web::Data<DbPool>,
+    item: web::Json<JsonBody>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data();
+    logging::set_uid(&input.uid);
+    let wage = Repository::new(&pool)
+        .run(move |connection| set_wage::execute(PayrollDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Created().json(wage))
+}
@@ -0,0 +1,121 @@
+//! RFC 7807 (application/problem+json) 形式のエラーレスポンス.
+use crate::database_utils::error::DataAccessError;
+use crate::logging;
+use crate::usecase::validation::FieldError;
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// データベースに接続できない場合に、再試行までの待ち時間として返す秒数.
+pub const RETRY_AFTER_SECONDS: u32 = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// 入力項目毎のエラー
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// 問い合わせ時にログと照合するためのid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, detail: Option<String>) -> Problem {
+        Problem {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail,
+            errors: vec![],
+            request_id: logging::current_request_id(),
+        }
+    }
+
+    pub fn to_response(&self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        HttpResponse::build(status)
+            .content_type(CONTENT_TYPE)
+            .json(self)
+    }
+}
+
+impl ResponseError for DataAccessError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DataAccessError::NotFound(_) => StatusCode::NOT_FOUND,
+            DataAccessError::Conflict(_) => StatusCode::CONFLICT,
+            DataAccessError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            DataAccessError::Forbidden(_) => StatusCode::FORBIDDEN,
+            DataAccessError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            DataAccessError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            DataAccessError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let problem = match self {
+            DataAccessError::Validation(errors) => Problem {
+                errors: errors.clone(),
+                ..Problem::new(
+                    self.status_code(),
+                    Some("The request contains invalid fields.".to_string()),
+                )
+            },
+            // データベースのエラーの内容は返さず、ログにのみ出力する
+            DataAccessError::Internal(_) | DataAccessError::Unavailable(_) => {
+                log::error!("{}", self);
+                Problem::new(self.status_code(), None)
+            }
+            _ => Problem::new(self.status_code(), Some(self.to_string())),
+        };
+
+        let mut response = problem.to_response();
+        if let DataAccessError::Unavailable(_) = self {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                header::HeaderValue::from(RETRY_AFTER_SECONDS),
+            );
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn status_code_test() {
+        let cases = vec![
+            (DataAccessError::NotFound("a".to_string()), 404),
+            (DataAccessError::Conflict("a".to_string()), 409),
+            (DataAccessError::Unauthorized("a".to_string()), 401),
+            (DataAccessError::Forbidden("a".to_string()), 403),
+            (DataAccessError::validation("a", "b"), 422),
+            (DataAccessError::Unavailable("a".to_string()), 503),
+            (DataAccessError::Internal(None), 500),
+        ];
+        for (error, status) in cases {
+            assert_eq!(error.status_code().as_u16(), status);
+            let response = error.error_response();
+            assert_eq!(response.status().as_u16(), status);
+            assert_eq!(
+                response.headers().get("content-type").unwrap(),
+                CONTENT_TYPE
+            );
+            assert_eq!(
+                response.headers().contains_key("retry-after"),
+                status == 503
+            );
+        }
+    }
+}
@@ -1,18 +1,21 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
 use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
 use crate::driver::users::UserDriver;
 use crate::usecase::users::delete::{self, InputData};
 use actix_web::{web, HttpResponse};
 
-pub async fn index(pool: web::Data<DbPool>, item: web::Json<InputData>) -> HttpResponse {
-    let connection = pool
-        .get()
-        .expect("couldn't get driver connection from pool");
-    let user_driver = UserDriver::new(&connection);
-    let input = item.into_inner();
+pub async fn index(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = InputData { uid: user.uid };
     let uid = input.uid.clone();
 
-    match delete::execute(user_driver, input) {
-        Ok(_v) => HttpResponse::Ok().body(format!("delete post [{}]", uid)),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    Repository::new(&pool)
+        .run(move |connection| delete::execute(UserDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Ok().body(format!("delete post [{}]", uid)))
 }
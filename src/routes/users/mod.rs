@@ -1,30 +1,39 @@
 pub mod delete;
 pub mod get;
+pub mod patch;
 pub mod post;
 
+use crate::auth::RequireScope;
+use crate::domain::entity::api_key::Scope;
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/")
+            .wrap(RequireScope::all(Scope::UsersAdmin))
             .route("", web::get().to(get::index))
             .route("", web::post().to(post::index))
-            .route("", web::delete().to(delete::index)),
+            .route("", web::delete().to(delete::index))
+            .route("", web::patch().to(patch::index)),
     );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::jwt::{self, test_util::bearer};
+    use crate::auth::test_util::authorize_all;
+    use crate::config;
     use crate::database_utils::pool::test_util::setup_connection_pool;
     use crate::domain::entity::user::User;
-    use crate::usecase;
+    use crate::routes;
     use actix_web::{test, web, App};
 
     /// # scenario
     ///
     /// 1. create
     /// 2. get
+    /// 3. update time zone
     /// 3. delete
     /// 3. not found
     #[actix_rt::test]
@@ -34,7 +43,10 @@ mod tests {
         let mut app = test::init_service(
             App::new()
                 .data(pool.clone())
-                .data(web::JsonConfig::default().limit(4096))
+                .app_data(web::Data::new(config::test_util::settings()))
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .app_data(routes::json_config(&config::test_util::settings().server))
                 .service(web::scope("").configure(config)),
         )
         .await;
@@ -43,30 +55,96 @@ mod tests {
 
         let req = test::TestRequest::post()
             .uri("/")
-            .set_json(&post::JsonBody::new(test_id.to_string())) // int max
+            .header("authorization", bearer(test_id))
+            .set_json(&post::JsonBody::default())
             .to_request();
         let resp: User = test::read_response_json(&mut app, req).await;
         assert_eq!(test_id, resp.uid);
+        assert_eq!(resp.time_zone, "Asia/Tokyo");
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .header("authorization", bearer(test_id))
+            .set_json(&patch::JsonBody {
+                time_zone: "America/New_York".to_string(),
+            })
+            .to_request();
+        let resp: User = test::read_response_json(&mut app, req).await;
+        assert_eq!(resp.time_zone, "America/New_York");
+
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .header("authorization", bearer(test_id))
+            .set_json(&patch::JsonBody {
+                time_zone: "Mars/Olympus_Mons".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 422);
+
+        // 登録済みのid
+        let req = test::TestRequest::post()
+            .uri("/")
+            .header("authorization", bearer(test_id))
+            .set_json(&post::JsonBody::default())
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 409);
 
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_id))
+            .uri("/")
+            .header("authorization", bearer(test_id))
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 200);
 
         let req = test::TestRequest::delete()
             .uri("/")
-            .set_json(&usecase::users::delete::InputData {
-                uid: test_id.to_string(),
-            })
+            .header("authorization", bearer(test_id))
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert!(resp.status().is_success());
 
+        let req = test::TestRequest::delete()
+            .uri("/")
+            .header("authorization", bearer(test_id))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 404);
+
         let req = test::TestRequest::get()
-            .uri(&format!("/?uid={}", test_id))
+            .uri("/")
+            .header("authorization", bearer(test_id))
             .to_request();
         let resp = test::call_service(&mut app, req).await;
         assert_eq!(resp.status().as_u16(), 204);
     }
+
+    #[actix_rt::test]
+    async fn json_limit_test() {
+        let mut settings = config::test_util::settings();
+        settings.server.json_limit = 16;
+
+        let mut app = test::init_service(
+            App::new()
+                .data(setup_connection_pool())
+                .app_data(web::Data::new(settings.clone()))
+                .app_data(jwt::test_util::verifier())
+                .wrap_fn(authorize_all)
+                .app_data(routes::json_config(&settings.server))
+                .service(web::scope("").configure(config)),
+        )
+        .await;
+
+        // server.json_limitを超えるbody
+        let req = test::TestRequest::patch()
+            .uri("/")
+            .header("authorization", bearer("json-limit-test"))
+            .set_json(&patch::JsonBody {
+                time_zone: "America/New_York".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert_eq!(resp.status().as_u16(), 413);
+    }
 }
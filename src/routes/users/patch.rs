@@ -0,0 +1,35 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::database_utils::error::DataAccessError;
+use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
+use crate::driver::users::UserDriver;
+use crate::usecase::users::update::{self, InputData};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonBody {
+    pub time_zone: String,
+}
+
+impl JsonBody {
+    pub fn to_input_data(&self, uid: String) -> InputData {
+        InputData {
+            uid,
+            time_zone: self.time_zone.clone(),
+        }
+    }
+}
+
+pub async fn index(
+    pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    item: web::Json<JsonBody>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid);
+    let user = Repository::new(&pool)
+        .run(move |connection| update::execute(UserDriver::new(connection), input))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
@@ -1,35 +1,43 @@
+use crate::auth::jwt::AuthenticatedUser;
+use crate::config::Settings;
+use crate::database_utils::error::DataAccessError;
 use crate::database_utils::pool::DbPool;
+use crate::database_utils::repository::Repository;
 use crate::driver::users::UserDriver;
 use crate::usecase::users::add::{self as add_user, InputData};
 use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct JsonBody {
-    uid: String,
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
 impl JsonBody {
-    #[cfg(test)]
-    pub fn new(uid: String) -> JsonBody {
-        JsonBody { uid }
-    }
-
-    pub fn to_input_data(&self) -> InputData {
+    /// time zoneを省略した場合は設定の初期値を使う.
+    pub fn to_input_data(&self, uid: String, default_time_zone: &str) -> InputData {
         InputData {
-            uid: self.uid.clone(),
+            uid,
+            time_zone: Some(
+                self.time_zone
+                    .clone()
+                    .unwrap_or_else(|| default_time_zone.to_string()),
+            ),
         }
     }
 }
 
-pub async fn index(pool: web::Data<DbPool>, item: web::Json<JsonBody>) -> HttpResponse {
-    let connection = pool
-        .get()
-        .expect("couldn't get driver connection from pool");
-    let user_driver = UserDriver::new(&connection);
+pub async fn index(
+    pool: web::Data<DbPool>,
+    settings: web::Data<Settings>,
+    user: AuthenticatedUser,
+    item: web::Json<JsonBody>,
+) -> Result<HttpResponse, DataAccessError> {
+    let input = item.to_input_data(user.uid, &settings.users.default_time_zone);
+    let user = Repository::new(&pool)
+        .run(move |connection| add_user::execute(UserDriver::new(connection), input))
+        .await?;
 
-    match add_user::execute(user_driver, item.to_input_data()) {
-        Ok(user) => HttpResponse::Created().json(user),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    Ok(HttpResponse::Created().json(user))
 }
@@ -1,10 +1,41 @@
+table! {
+    api_keys (id) {
+        id -> Int4,
+        name -> Varchar,
+        prefix -> Varchar,
+        key_hash -> Varchar,
+        scopes -> Array<Text>,
+        expires_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     attendance_records (id) {
         id -> Int4,
         user_id -> Int4,
-        start_time -> Timestamp,
-        end_time -> Timestamp,
-        break_time -> Int4,
+        start_time -> Timestamptz,
+        end_time -> Nullable<Timestamptz>,
+        note -> Nullable<Text>,
+    }
+}
+
+table! {
+    breaks (id) {
+        id -> Int4,
+        record_id -> Int4,
+        start_time -> Timestamptz,
+        end_time -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    hourly_wages (id) {
+        id -> Int4,
+        user_id -> Int4,
+        amount -> Int4,
+        effective_from -> Date,
     }
 }
 
@@ -12,9 +43,13 @@ table! {
     users (id) {
         id -> Int4,
         uid -> Varchar,
+        time_zone -> Varchar,
+        deactivated_at -> Nullable<Timestamptz>,
     }
 }
 
 joinable!(attendance_records -> users (user_id));
+joinable!(breaks -> attendance_records (record_id));
+joinable!(hourly_wages -> users (user_id));
 
-allow_tables_to_appear_in_same_query!(attendance_records, users,);
+allow_tables_to_appear_in_same_query!(api_keys, attendance_records, breaks, hourly_wages, users,);
@@ -0,0 +1,20 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::api_key::{hash_key, ApiKey};
+use chrono::{DateTime, Utc};
+
+pub trait AuthenticateUseCase {
+    fn find_by_hash(&self, key_hash: String) -> Result<Option<ApiKey>, DataAccessError>;
+}
+
+/// 有効なapi keyか. 存在しない鍵と期限切れ・失効した鍵は区別しない.
+pub fn execute<T>(data_access: T, key: &str, now: DateTime<Utc>) -> Result<ApiKey, DataAccessError>
+where
+    T: AuthenticateUseCase,
+{
+    match data_access.find_by_hash(hash_key(key))? {
+        Some(api_key) if api_key.is_active(now) => Ok(api_key),
+        _ => Err(DataAccessError::Unauthorized(
+            "Invalid api key!".to_string(),
+        )),
+    }
+}
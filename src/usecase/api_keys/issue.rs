@@ -0,0 +1,101 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::api_key::{IssuedApiKey, Scope};
+use crate::usecase::validation::Validator;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 名前の最大文字数.
+pub const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    /// 利用するクライアントの名前
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// 有効期限(unix time). 未指定の場合は無期限
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl InputData {
+    pub fn validate(&self, now: i64) -> Result<Vec<Scope>, DataAccessError> {
+        let mut validator = Validator::new();
+        if self.name.trim().is_empty() {
+            validator.add("name", "must not be empty");
+        } else if self.name.chars().count() > MAX_NAME_LENGTH {
+            validator.add("name", "must be at most 100 characters");
+        }
+
+        if self.scopes.is_empty() {
+            validator.add("scopes", "must not be empty");
+        }
+        let mut scopes = Vec::new();
+        for (index, scope) in self.scopes.iter().enumerate() {
+            match scope.parse::<Scope>() {
+                Ok(scope) => scopes.push(scope),
+                Err(message) => validator.add(&format!("scopes[{}]", index), &message),
+            }
+        }
+
+        if let Some(expires_at) = self.expires_at {
+            if expires_at <= now {
+                validator.add("expires_at", "must be in the future");
+            }
+        }
+
+        validator.finish().map(|_| scopes)
+    }
+}
+
+pub trait IssueApiKeyUseCase {
+    fn issue(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<IssuedApiKey, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<IssuedApiKey, DataAccessError>
+where
+    T: IssueApiKeyUseCase,
+{
+    let scopes = input.validate(Utc::now().timestamp())?;
+
+    data_access.issue(
+        input.name,
+        scopes,
+        input
+            .expires_at
+            .map(|expires_at| Utc.timestamp(expires_at, 0)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate_test() {
+        let now = Utc::now().timestamp();
+        let input = InputData {
+            name: "payroll batch".to_string(),
+            scopes: vec!["records:read".to_string()],
+            expires_at: Some(now + 60),
+        };
+        assert_eq!(input.validate(now).unwrap(), vec![Scope::RecordsRead]);
+
+        let input = InputData {
+            name: " ".to_string(),
+            scopes: vec!["records:read".to_string(), "records:delete".to_string()],
+            expires_at: Some(now),
+        };
+        match input.validate(now) {
+            Err(DataAccessError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(fields, vec!["name", "scopes[1]", "expires_at"]);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
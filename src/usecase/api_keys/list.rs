@@ -0,0 +1,13 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::api_key::ApiKey;
+
+pub trait ListApiKeysUseCase {
+    fn list(&self) -> Result<Vec<ApiKey>, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T) -> Result<Vec<ApiKey>, DataAccessError>
+where
+    T: ListApiKeysUseCase,
+{
+    data_access.list()
+}
@@ -0,0 +1,5 @@
+pub mod authenticate;
+pub mod issue;
+pub mod list;
+pub mod revoke;
+pub mod rotate;
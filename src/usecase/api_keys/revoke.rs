@@ -0,0 +1,20 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::api_key::ApiKey;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub id: i32,
+}
+
+pub trait RevokeApiKeyUseCase {
+    /// 失効させる. 失効済みの場合はそのまま返す.
+    fn revoke(&self, id: i32) -> Result<ApiKey, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<ApiKey, DataAccessError>
+where
+    T: RevokeApiKeyUseCase,
+{
+    data_access.revoke(input.id)
+}
@@ -0,0 +1,50 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::api_key::IssuedApiKey;
+use crate::usecase::validation::Validator;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 古い鍵を使用できる期間の初期値(秒). クライアントが新しい鍵に切り替えるための猶予.
+pub const DEFAULT_GRACE_SECONDS: i64 = 24 * 60 * 60;
+/// 古い鍵を使用できる期間の上限(秒).
+pub const MAX_GRACE_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub id: i32,
+    /// 古い鍵を使用できる期間(秒). 0の場合は即座に使用できなくする
+    #[serde(default)]
+    pub grace_seconds: Option<i64>,
+}
+
+impl InputData {
+    pub fn validate(&self) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        if let Some(grace_seconds) = self.grace_seconds {
+            if !(0..=MAX_GRACE_SECONDS).contains(&grace_seconds) {
+                validator.add("grace_seconds", "must be between 0 and 30 days");
+            }
+        }
+
+        validator.finish()
+    }
+}
+
+pub trait RotateApiKeyUseCase {
+    /// 同じ名前・scopeの鍵を発行し、古い鍵は`old_key_expires_at`までに期限切れとする.
+    fn rotate(
+        &self,
+        id: i32,
+        old_key_expires_at: DateTime<Utc>,
+    ) -> Result<IssuedApiKey, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<IssuedApiKey, DataAccessError>
+where
+    T: RotateApiKeyUseCase,
+{
+    input.validate()?;
+
+    let grace_seconds = input.grace_seconds.unwrap_or(DEFAULT_GRACE_SECONDS);
+    data_access.rotate(input.id, Utc::now() + Duration::seconds(grace_seconds))
+}
@@ -1,13 +1,35 @@
 use crate::database_utils::error::DataAccessError;
 use crate::domain::entity::attendance_record::AttendanceRecord;
+use crate::usecase::validation::Validator;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakInput {
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InputData {
     pub uid: String,
     pub start_time: i64,
     pub end_time: i64,
-    pub break_time: i32,
+    pub breaks: Vec<BreakInput>,
+    pub note: Option<String>,
+}
+
+impl InputData {
+    pub fn validate(&self, now: i64) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        validator.timestamp("start_time", self.start_time, now);
+        validator.timestamp("end_time", self.end_time, now);
+        validator.shift(self.start_time, self.end_time);
+        validator.breaks(&self.breaks, Some((self.start_time, self.end_time)), now);
+        validator.note(&self.note);
+
+        validator.finish()
+    }
 }
 
 pub trait AddRecordUseCase {
@@ -18,5 +40,7 @@ pub fn execute<T>(data_access: T, input: InputData) -> Result<AttendanceRecord,
 where
     T: AddRecordUseCase,
 {
+    input.validate(Utc::now().timestamp())?;
+
     data_access.add_record(input)
 }
@@ -0,0 +1,21 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+}
+
+/// 休憩終了打刻.
+/// 休憩中でない場合はエラーとする.
+pub trait BreakEndUseCase {
+    fn break_end(&self, input: InputData) -> Result<AttendanceRecord, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<AttendanceRecord, DataAccessError>
+where
+    T: BreakEndUseCase,
+{
+    data_access.break_end(input)
+}
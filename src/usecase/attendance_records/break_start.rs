@@ -0,0 +1,21 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+}
+
+/// 休憩開始打刻.
+/// 出勤中のレコードが存在しない場合、既に休憩中の場合はエラーとする.
+pub trait BreakStartUseCase {
+    fn break_start(&self, input: InputData) -> Result<AttendanceRecord, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<AttendanceRecord, DataAccessError>
+where
+    T: BreakStartUseCase,
+{
+    data_access.break_start(input)
+}
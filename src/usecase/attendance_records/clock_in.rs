@@ -0,0 +1,21 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+}
+
+/// 出勤打刻.
+/// 退勤していないレコードが既に存在する場合はエラーとする.
+pub trait ClockInUseCase {
+    fn clock_in(&self, input: InputData) -> Result<AttendanceRecord, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<AttendanceRecord, DataAccessError>
+where
+    T: ClockInUseCase,
+{
+    data_access.clock_in(input)
+}
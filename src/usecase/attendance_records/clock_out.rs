@@ -0,0 +1,21 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+}
+
+/// 退勤打刻.
+/// 出勤中のレコードが存在しない場合、休憩中の場合はエラーとする.
+pub trait ClockOutUseCase {
+    fn clock_out(&self, input: InputData) -> Result<AttendanceRecord, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<AttendanceRecord, DataAccessError>
+where
+    T: ClockOutUseCase,
+{
+    data_access.clock_out(input)
+}
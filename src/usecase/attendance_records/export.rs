@@ -0,0 +1,305 @@
+//! 勤怠記録のCSV出力.
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::attendance_record::AttendanceRecord;
+use crate::domain::entity::working_time::WorkingTime;
+use crate::usecase::validation::Validator;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// 1回の取得で読み込むレコード数.
+pub const BATCH_SIZE: i64 = 500;
+/// 1回の出力で指定できる期間の上限(日).
+pub const MAX_EXPORT_DAYS: i64 = 366;
+/// Excelが文字コードをUTF-8と判定するための先頭のBOM.
+pub const BOM: &str = "\u{feff}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Uid,
+    /// 出勤日. ユーザーのtime zoneにおける日付
+    Date,
+    StartTime,
+    EndTime,
+    BreakMinutes,
+    WorkedMinutes,
+    OvertimeMinutes,
+    LateNightMinutes,
+    HolidayMinutes,
+    Note,
+}
+
+impl Column {
+    pub const ALL: [Column; 10] = [
+        Column::Uid,
+        Column::Date,
+        Column::StartTime,
+        Column::EndTime,
+        Column::BreakMinutes,
+        Column::WorkedMinutes,
+        Column::OvertimeMinutes,
+        Column::LateNightMinutes,
+        Column::HolidayMinutes,
+        Column::Note,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Column::Uid => "uid",
+            Column::Date => "date",
+            Column::StartTime => "start_time",
+            Column::EndTime => "end_time",
+            Column::BreakMinutes => "break_minutes",
+            Column::WorkedMinutes => "worked_minutes",
+            Column::OvertimeMinutes => "overtime_minutes",
+            Column::LateNightMinutes => "late_night_minutes",
+            Column::HolidayMinutes => "holiday_minutes",
+            Column::Note => "note",
+        }
+    }
+
+    /// カンマ区切りの列名.
+    pub fn parse_list(value: &str) -> Result<Vec<Column>, String> {
+        value
+            .split(',')
+            .map(|name| {
+                Column::ALL
+                    .iter()
+                    .find(|column| column.as_str() == name.trim())
+                    .copied()
+                    .ok_or_else(|| format!("unknown column: {}", name))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputData {
+    /// 指定しない場合は全ユーザー
+    pub uid: Option<String>,
+    /// 出勤時刻がfrom以降のレコード
+    pub from: i64,
+    /// 出勤時刻がtoより前のレコード
+    pub to: i64,
+    pub columns: Vec<Column>,
+}
+
+impl InputData {
+    pub fn validate(&self, now: i64) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        validator.period(self.from, self.to, now);
+        let max_span = MAX_EXPORT_DAYS * 24 * 60 * 60;
+        if matches!(self.to.checked_sub(self.from), Some(span) if span > max_span) {
+            validator.add(
+                "to",
+                &format!("must be within {} days of from", MAX_EXPORT_DAYS),
+            );
+        }
+        if self.columns.is_empty() {
+            validator.add("columns", "must not be empty");
+        }
+        validator.finish()
+    }
+}
+
+/// 出力する1行. 日時はユーザーのtime zoneで表示する.
+#[derive(Debug)]
+pub struct ExportRow {
+    pub uid: String,
+    pub time_zone: Tz,
+    pub record: AttendanceRecord,
+}
+
+impl ExportRow {
+    fn value(&self, column: Column) -> String {
+        let local = |datetime: DateTime<Utc>| datetime.with_timezone(&self.time_zone);
+        let working_time = self.record.working_time.as_ref();
+        let minutes =
+            |f: fn(&WorkingTime) -> i64| working_time.map(|w| f(w).to_string()).unwrap_or_default();
+
+        match column {
+            Column::Uid => self.uid.clone(),
+            Column::Date => local(self.record.start_time).format("%Y-%m-%d").to_string(),
+            Column::StartTime => local(self.record.start_time)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            Column::EndTime => self
+                .record
+                .end_time
+                .map(|end_time| local(end_time).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            Column::BreakMinutes => (self.record.break_seconds / 60).to_string(),
+            Column::WorkedMinutes => minutes(|w| w.worked_minutes),
+            Column::OvertimeMinutes => minutes(|w| w.overtime_minutes),
+            Column::LateNightMinutes => minutes(|w| w.late_night_minutes),
+            Column::HolidayMinutes => minutes(|w| w.holiday_minutes),
+            Column::Note => self.record.note.clone().unwrap_or_default(),
+        }
+    }
+
+    /// RFC 4180形式の1行. 改行はCRLF.
+    pub fn to_csv(&self, columns: &[Column]) -> String {
+        csv_line(columns.iter().map(|column| self.value(*column)))
+    }
+}
+
+/// 列名の行.
+pub fn header(columns: &[Column]) -> String {
+    csv_line(columns.iter().map(|column| column.as_str().to_string()))
+}
+
+fn csv_line(values: impl Iterator<Item = String>) -> String {
+    let mut line = values
+        .map(|value| {
+            if value.contains(&[',', '"', '\r', '\n'][..]) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// 直前に出力したレコードの位置. ユーザー、出勤時刻、idの順に出力する.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportCursor {
+    pub user_id: i32,
+    pub start_time: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl ExportCursor {
+    pub fn of(row: &ExportRow) -> ExportCursor {
+        ExportCursor {
+            user_id: row.record.user_id,
+            start_time: row.record.start_time,
+            id: row.record.id,
+        }
+    }
+}
+
+pub trait ExportRecordsUseCase {
+    /// `after`より後のレコードを最大`limit`件取得する.
+    fn export_batch(
+        &self,
+        input: &InputData,
+        after: Option<ExportCursor>,
+        limit: i64,
+    ) -> Result<Vec<ExportRow>, DataAccessError>;
+}
+
+/// 次の`BATCH_SIZE`件を取得する. 返した件数が`BATCH_SIZE`未満であれば最後.
+pub fn execute<T>(
+    data_access: T,
+    input: &InputData,
+    after: Option<ExportCursor>,
+) -> Result<Vec<ExportRow>, DataAccessError>
+where
+    T: ExportRecordsUseCase,
+{
+    input.validate(Utc::now().timestamp())?;
+    data_access.export_batch(input, after, BATCH_SIZE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    const NOW: i64 = 1_605_000_000;
+
+    #[test]
+    fn to_csv_test() {
+        let start_time = Utc.ymd(2020, 11, 1).and_hms(15, 30, 0);
+        let mut record = AttendanceRecord::new(
+            1,
+            1,
+            start_time,
+            Some(start_time + chrono::Duration::hours(10)),
+            vec![],
+        );
+        record.note = Some("打刻漏れ, \"修正\"".to_string());
+        record.working_time = Some(WorkingTime {
+            worked_minutes: 600,
+            overtime_minutes: 120,
+            ..WorkingTime::default()
+        });
+        let row = ExportRow {
+            uid: "alice".to_string(),
+            time_zone: chrono_tz::Asia::Tokyo,
+            record,
+        };
+
+        let columns =
+            Column::parse_list("uid,date,start_time,end_time,worked_minutes,overtime_minutes,note")
+                .unwrap();
+        assert_eq!(
+            header(&columns),
+            "uid,date,start_time,end_time,worked_minutes,overtime_minutes,note\r\n"
+        );
+        // 日付・時刻はAsia/Tokyoで表示する
+        assert_eq!(
+            row.to_csv(&columns),
+            "alice,2020-11-02,2020-11-02 00:30:00,2020-11-02 10:30:00,600,120,\"打刻漏れ, \"\"修正\"\"\"\r\n"
+        );
+
+        // 退勤前のレコードは終了時刻と労働時間を空にする
+        let open = ExportRow {
+            record: AttendanceRecord::new(2, 1, start_time, None, vec![]),
+            ..row
+        };
+        assert_eq!(
+            open.to_csv(&columns),
+            "alice,2020-11-02,2020-11-02 00:30:00,,,,\r\n"
+        );
+
+        assert!(Column::parse_list("uid,salary").is_err());
+    }
+
+    #[test]
+    fn validate_test() {
+        let input = InputData {
+            uid: None,
+            from: NOW - MAX_EXPORT_DAYS * 24 * 60 * 60,
+            to: NOW,
+            columns: Column::ALL.to_vec(),
+        };
+        assert!(input.validate(NOW).is_ok());
+        assert!(InputData {
+            to: 0,
+            ..input.clone()
+        }
+        .validate(NOW)
+        .is_err());
+        assert!(InputData {
+            from: input.from - 1,
+            ..input.clone()
+        }
+        .validate(NOW)
+        .is_err());
+        // 日時に変換できない期間
+        assert!(InputData {
+            from: 99_999_999_999_999_999,
+            to: 100_000_000_000_000_000,
+            ..input.clone()
+        }
+        .validate(NOW)
+        .is_err());
+        assert!(InputData {
+            from: i64::MIN,
+            ..input.clone()
+        }
+        .validate(NOW)
+        .is_err());
+        assert!(InputData {
+            columns: vec![],
+            ..input
+        }
+        .validate(NOW)
+        .is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use super::{ImportRow, Importer};
+use crate::database_utils::error::DataAccessError;
+use crate::usecase::attendance_records::add::{self, BreakInput};
+use crate::usecase::validation::FieldError;
+use serde::Deserialize;
+
+/// 1行に1件の`POST /attendance_records`と同じJSON. 空行は読み飛ばす.
+#[derive(Debug, Default)]
+pub struct JsonLines;
+
+/// 休憩と備考は省略できる.
+#[derive(Debug, Deserialize)]
+struct JsonRow {
+    uid: String,
+    start_time: i64,
+    end_time: i64,
+    #[serde(default)]
+    breaks: Vec<BreakInput>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+impl Importer for JsonLines {
+    fn read(&self, body: &str) -> Result<Vec<ImportRow>, DataAccessError> {
+        Ok(body
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| ImportRow {
+                line: index as u64 + 1,
+                input: serde_json::from_str::<JsonRow>(line)
+                    .map(|row| add::InputData {
+                        uid: row.uid,
+                        start_time: row.start_time,
+                        end_time: row.end_time,
+                        breaks: row.breaks,
+                        note: row.note,
+                    })
+                    .map_err(|e| vec![FieldError::new("line", &e.to_string())]),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::super::test_util::{errors, lines, NOW};
+    use super::*;
+
+    #[test]
+    fn read_test() {
+        let rows = parse(
+            &JsonLines,
+            include_str!("../../../../testdata/import/records.jsonl"),
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(lines(&rows), vec![1, 3, 4]);
+        assert!(rows[0].input.is_ok());
+        // 休憩が勤務時間の外
+        assert_eq!(errors(&rows[1]), vec!["breaks[0].start_time"]);
+        assert_eq!(errors(&rows[2]), vec!["line"]);
+    }
+}
@@ -0,0 +1,164 @@
+//! よく使われる勤怠サービスの出力形式. いずれも`ColumnMapping`で定義する.
+use super::mapping::ColumnMapping;
+
+/// 1日1行、休憩の開始・終了を記載する形式.
+pub const DAILY_JP: &str = "daily_jp";
+/// 1日1行、休憩時間の合計を`H:MM`で記載する形式. 日付は`2020-10-01`.
+pub const TIMESHEET_JP: &str = "timesheet_jp";
+/// 英語の列名で、日付が`MM/DD/YYYY`、休憩時間の合計を分で記載する形式.
+pub const PUNCH_EN: &str = "punch_en";
+
+fn mapping(uid: &str, date: &str, start_time: &str, end_time: &str) -> ColumnMapping {
+    ColumnMapping {
+        uid: uid.to_string(),
+        date: Some(date.to_string()),
+        start_time: start_time.to_string(),
+        end_time: end_time.to_string(),
+        break_start: None,
+        break_end: None,
+        break_duration: None,
+        note: None,
+        date_format: "%Y/%m/%d".to_string(),
+        time_format: "%H:%M".to_string(),
+        time_zone: Some("Asia/Tokyo".to_string()),
+        delimiter: ',',
+    }
+}
+
+/// 組み込みの形式の対応.
+pub fn preset(name: &str) -> Option<ColumnMapping> {
+    match name {
+        DAILY_JP => Some(ColumnMapping {
+            break_start: Some("休憩開始".to_string()),
+            break_end: Some("休憩終了".to_string()),
+            note: Some("備考".to_string()),
+            ..mapping("従業員コード", "日付", "出勤時刻", "退勤時刻")
+        }),
+        TIMESHEET_JP => Some(ColumnMapping {
+            break_duration: Some("休憩時間".to_string()),
+            note: Some("メモ".to_string()),
+            date_format: "%Y-%m-%d".to_string(),
+            ..mapping("社員番号", "勤務日", "始業", "終業")
+        }),
+        PUNCH_EN => Some(ColumnMapping {
+            break_duration: Some("Break (min)".to_string()),
+            note: Some("Notes".to_string()),
+            date_format: "%m/%d/%Y".to_string(),
+            time_zone: Some("UTC".to_string()),
+            ..mapping("Employee ID", "Date", "Clock In", "Clock Out")
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_util::{errors, lines, NOW};
+    use super::super::{parse, ImportRow, MappedCsv};
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Asia::Tokyo;
+
+    fn read(name: &str, body: &str) -> Vec<ImportRow> {
+        let mapping = preset(name).unwrap();
+        assert!(mapping.validate().is_empty());
+        parse(&MappedCsv::new(mapping), body, NOW).unwrap()
+    }
+
+    #[test]
+    fn daily_jp_test() {
+        let rows = read(
+            DAILY_JP,
+            include_str!("../../../../testdata/import/daily_jp.csv"),
+        );
+        // 休日の行は読み飛ばす
+        assert_eq!(lines(&rows), vec![2, 4, 5]);
+
+        let input = rows[0].input.as_ref().unwrap();
+        assert_eq!(input.uid, "E001");
+        assert_eq!(
+            input.start_time,
+            Tokyo.ymd(2020, 10, 1).and_hms(9, 0, 0).timestamp()
+        );
+        assert_eq!(input.breaks.len(), 1);
+        assert_eq!(
+            input.breaks[0].end_time,
+            Tokyo.ymd(2020, 10, 1).and_hms(13, 0, 0).timestamp()
+        );
+        assert_eq!(input.note, Some("打刻修正".to_string()));
+
+        // 日をまたぐ勤務. 休憩と退勤は翌日
+        let input = rows[1].input.as_ref().unwrap();
+        assert_eq!(
+            input.end_time,
+            Tokyo.ymd(2020, 10, 4).and_hms(6, 0, 0).timestamp()
+        );
+        assert_eq!(
+            input.breaks[0].start_time,
+            Tokyo.ymd(2020, 10, 4).and_hms(1, 0, 0).timestamp()
+        );
+
+        assert_eq!(errors(&rows[2]), vec!["date"]);
+    }
+
+    #[test]
+    fn timesheet_jp_test() {
+        let rows = read(
+            TIMESHEET_JP,
+            include_str!("../../../../testdata/import/timesheet_jp.csv"),
+        );
+        assert_eq!(lines(&rows), vec![2, 3, 4]);
+
+        // 休憩1時間を勤務時間の中央に置く
+        let input = rows[0].input.as_ref().unwrap();
+        assert_eq!(input.uid, "1001");
+        assert_eq!(
+            input.breaks[0].start_time,
+            Tokyo.ymd(2020, 10, 1).and_hms(13, 0, 0).timestamp()
+        );
+        assert_eq!(
+            input.breaks[0].end_time - input.breaks[0].start_time,
+            60 * 60
+        );
+        assert!(input.note.is_none());
+
+        // 24時以降の表記
+        let input = rows[1].input.as_ref().unwrap();
+        assert_eq!(
+            input.end_time,
+            Tokyo.ymd(2020, 10, 3).and_hms(1, 30, 0).timestamp()
+        );
+        assert_eq!(input.note, Some("棚卸".to_string()));
+
+        assert_eq!(errors(&rows[2]), vec!["breaks[0]"]);
+    }
+
+    #[test]
+    fn punch_en_test() {
+        let rows = read(
+            PUNCH_EN,
+            include_str!("../../../../testdata/import/punch_en.csv"),
+        );
+        assert_eq!(lines(&rows), vec![2, 3, 4, 5, 6, 7]);
+
+        let input = rows[0].input.as_ref().unwrap();
+        assert_eq!(input.uid, "bob");
+        assert_eq!(
+            input.start_time,
+            chrono::Utc.ymd(2020, 10, 1).and_hms(8, 30, 0).timestamp()
+        );
+        assert_eq!(
+            input.breaks[0].end_time - input.breaks[0].start_time,
+            30 * 60
+        );
+        assert_eq!(input.note, Some("onsite, day 1".to_string()));
+
+        // 退勤が無い
+        assert_eq!(errors(&rows[1]), vec!["end_time"]);
+        // 桁の大きい休憩時間と退勤時刻. 勤務時間より長い休憩
+        assert_eq!(errors(&rows[2]), vec!["breaks[0]"]);
+        assert_eq!(errors(&rows[3]), vec!["end_time"]);
+        assert_eq!(errors(&rows[4]), vec!["breaks[0]"]);
+        assert!(!errors(&rows[5]).is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+use super::{read_headers, read_records, ImportRow, Importer};
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::user::{validate_time_zone, DEFAULT_TIME_ZONE};
+use crate::domain::entity::working_time::local_instant;
+use crate::usecase::attendance_records::add::{self, BreakInput};
+use crate::usecase::validation::FieldError;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// CSVの列と勤怠記録の項目の対応. 設定ファイルの`[import.mappings.<名前>]`に記載する.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnMapping {
+    /// uidの列
+    pub uid: String,
+    /// 勤務日の列. 省略した場合は出勤・退勤などの列に日付を含む
+    #[serde(default)]
+    pub date: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    #[serde(default)]
+    pub break_start: Option<String>,
+    #[serde(default)]
+    pub break_end: Option<String>,
+    /// 休憩時間の合計の列. `60`(分)または`1:00`. 休憩は勤務時間の中央に置く
+    #[serde(default)]
+    pub break_duration: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// `date`を省略した場合は日付を含む形式
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    /// 日時を解釈するIANA time zone名. 設定ファイルで省略した場合は`users.default_time_zone`
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+}
+
+fn default_date_format() -> String {
+    "%Y/%m/%d".to_string()
+}
+
+fn default_time_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+impl ColumnMapping {
+    /// 設定として不正な箇所.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Some(Err(message)) = self.time_zone.as_deref().map(validate_time_zone) {
+            errors.push(format!("time_zone {}", message));
+        }
+        if self.break_start.is_some() != self.break_end.is_some() {
+            errors.push("break_start and break_end must be set together".to_string());
+        }
+        if self.break_start.is_some() && self.break_duration.is_some() {
+            errors.push("break_duration cannot be used with break_start".to_string());
+        }
+        if !self.delimiter.is_ascii() {
+            errors.push("delimiter must be an ASCII character".to_string());
+        }
+        // 日付の列が無い場合、出勤・退勤などの列は日時として読む
+        if self.date.is_none() && !includes_date(&self.time_format) {
+            errors.push("time_format must include the date when date is not set".to_string());
+        }
+        errors
+    }
+
+    /// 対応付けた列の名前.
+    fn columns(&self) -> Vec<&str> {
+        let mut columns = vec![
+            self.uid.as_str(),
+            self.start_time.as_str(),
+            self.end_time.as_str(),
+        ];
+        columns.extend(
+            [
+                &self.date,
+                &self.break_start,
+                &self.break_end,
+                &self.break_duration,
+                &self.note,
+            ]
+            .iter()
+            .filter_map(|column| column.as_deref()),
+        );
+        columns
+    }
+}
+
+/// 日時を書式どおりに出力し、日時として読み戻せるか確かめる.
+fn includes_date(format: &str) -> bool {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return false;
+    }
+    let sample = NaiveDate::from_ymd(2020, 10, 1).and_hms(9, 30, 0);
+    NaiveDateTime::parse_from_str(&sample.format(format).to_string(), format) == Ok(sample)
+}
+
+/// `ColumnMapping`に従って読み取るCSV.
+///
+/// 出勤と退勤の両方が空の行(休日など)は読み飛ばす. 日付の列がある場合、
+/// 前の時刻より早い時刻や`25:30`のような24時以降の時刻は翌日とみなす.
+#[derive(Debug)]
+pub struct MappedCsv {
+    mapping: ColumnMapping,
+    time_zone: Tz,
+}
+
+impl MappedCsv {
+    pub fn new(mapping: ColumnMapping) -> MappedCsv {
+        let time_zone = mapping
+            .time_zone
+            .as_deref()
+            .and_then(|time_zone| time_zone.parse().ok())
+            .unwrap_or(DEFAULT_TIME_ZONE);
+        MappedCsv { mapping, time_zone }
+    }
+
+    /// 日付の列がある場合は時刻のみ、無い場合は日時として読む. `after`より前の場合は翌日とする.
+    ///
+    /// 読めない値や、西暦1年から9999年の範囲外の日時は`None`.
+    fn datetime(
+        &self,
+        date: Option<NaiveDate>,
+        value: &str,
+        after: Option<NaiveDateTime>,
+    ) -> Option<NaiveDateTime> {
+        let datetime = match date {
+            Some(date) => {
+                let (time, days) = parse_clock(value, &self.mapping.time_format)?;
+                date.and_time(time)
+                    .checked_add_signed(Duration::days(days))?
+            }
+            None => NaiveDateTime::parse_from_str(value, &self.mapping.time_format).ok()?,
+        };
+        let datetime = match after {
+            Some(after) if self.mapping.date.is_some() && datetime < after => {
+                datetime.checked_add_signed(Duration::days(1))?
+            }
+            _ => datetime,
+        };
+        // time zoneの変換で桁あふれしない範囲
+        Some(datetime).filter(|datetime| (1..=9999).contains(&datetime.year()))
+    }
+
+    fn timestamp(&self, datetime: NaiveDateTime) -> i64 {
+        local_instant(&self.time_zone, datetime).timestamp()
+    }
+
+    fn convert(&self, row: &Row) -> Option<Result<add::InputData, Vec<FieldError>>> {
+        let mapping = &self.mapping;
+        let start_value = row.get(&mapping.start_time);
+        let end_value = row.get(&mapping.end_time);
+        if start_value.is_empty() && end_value.is_empty() {
+            return None;
+        }
+        let invalid = |field: &str, column: &str| {
+            FieldError::new(
+                field,
+                &format!("invalid value in {}: {}", column, row.get(column)),
+            )
+        };
+
+        let date = match &mapping.date {
+            Some(column) => {
+                match NaiveDate::parse_from_str(row.get(column), &mapping.date_format) {
+                    Ok(date) => Some(date),
+                    Err(_) => return Some(Err(vec![invalid("date", column)])),
+                }
+            }
+            None => None,
+        };
+
+        let mut errors = Vec::new();
+        let start = self.datetime(date, start_value, None);
+        if start.is_none() {
+            errors.push(invalid("start_time", &mapping.start_time));
+        }
+        let end = self.datetime(date, end_value, start);
+        if end.is_none() {
+            errors.push(invalid("end_time", &mapping.end_time));
+        }
+
+        let mut breaks = Vec::new();
+        if let (Some(start_column), Some(end_column)) = (&mapping.break_start, &mapping.break_end) {
+            let (break_start, break_end) = (row.get(start_column), row.get(end_column));
+            if !break_start.is_empty() || !break_end.is_empty() {
+                let break_start = self.datetime(date, break_start, start);
+                let break_end = self.datetime(date, break_end, break_start);
+                match (break_start, break_end) {
+                    (Some(break_start), Some(break_end)) => breaks.push((break_start, break_end)),
+                    (None, _) => errors.push(invalid("breaks[0].start_time", start_column)),
+                    (_, None) => errors.push(invalid("breaks[0].end_time", end_column)),
+                }
+            }
+        }
+        if let Some(column) = &mapping.break_duration {
+            match (parse_duration(row.get(column)), start, end) {
+                (Some(duration), _, _) if duration == Duration::zero() => {}
+                // 勤務時間の中央に置く. 勤務時間より長い休憩は不正
+                (Some(duration), Some(start), Some(end)) if end > start => {
+                    if duration < end - start {
+                        let break_start = start + (end - start - duration) / 2;
+                        breaks.push((break_start, break_start + duration));
+                    } else {
+                        errors.push(invalid("breaks[0]", column));
+                    }
+                }
+                (Some(_), _, _) => {}
+                (None, _, _) => errors.push(invalid("breaks[0]", column)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Some(Err(errors));
+        }
+        let (start, end) = (start?, end?);
+        Some(Ok(add::InputData {
+            uid: row.get(&mapping.uid).to_string(),
+            start_time: self.timestamp(start),
+            end_time: self.timestamp(end),
+            breaks: breaks
+                .into_iter()
+                .map(|(break_start, break_end)| BreakInput {
+                    start_time: self.timestamp(break_start),
+                    end_time: self.timestamp(break_end),
+                })
+                .collect(),
+            note: mapping
+                .note
+                .as_ref()
+                .map(|column| row.get(column).to_string())
+                .filter(|note| !note.is_empty()),
+        }))
+    }
+}
+
+/// 列名で値を取得する行.
+struct Row<'a> {
+    headers: &'a csv::StringRecord,
+    record: &'a csv::StringRecord,
+}
+
+impl Row<'_> {
+    fn get(&self, column: &str) -> &str {
+        self.headers
+            .iter()
+            .position(|header| header == column)
+            .and_then(|index| self.record.get(index))
+            .unwrap_or("")
+    }
+}
+
+/// 24時以降の表記で受け付ける時の上限(含まない).
+const MAX_CLOCK_HOURS: u32 = 48;
+
+/// 時刻と、24時以降の場合の日数.
+fn parse_clock(value: &str, format: &str) -> Option<(NaiveTime, i64)> {
+    NaiveTime::parse_from_str(value, format)
+        .ok()
+        .map(|time| (time, 0))
+        .or_else(|| {
+            let (hours, minutes) = value.split_once(':')?;
+            let (hours, minutes) = (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?);
+            if hours >= MAX_CLOCK_HOURS {
+                return None;
+            }
+            let time = NaiveTime::from_hms_opt(hours % 24, minutes, 0)?;
+            Some((time, (hours / 24) as i64))
+        })
+}
+
+/// `60`(分)、または`1:00`(時:分). 空の場合は0. `MAX_CLOCK_HOURS`時間以上は不正.
+fn parse_duration(value: &str) -> Option<Duration> {
+    if value.is_empty() {
+        return Some(Duration::zero());
+    }
+    let minutes = match value.split_once(':') {
+        Some((hours, minutes)) => hours
+            .parse::<i64>()
+            .ok()?
+            .checked_mul(60)?
+            .checked_add(minutes.parse::<i64>().ok()?)?,
+        None => value.parse::<i64>().ok()?,
+    };
+    if minutes < 0 || minutes >= i64::from(MAX_CLOCK_HOURS) * 60 {
+        return None;
+    }
+    Some(Duration::minutes(minutes))
+}
+
+impl Importer for MappedCsv {
+    fn read(&self, body: &str) -> Result<Vec<ImportRow>, DataAccessError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(self.mapping.delimiter as u8)
+            .trim(csv::Trim::All)
+            .from_reader(body.as_bytes());
+        let headers = read_headers(&mut reader, &self.mapping.columns())?;
+
+        Ok(read_records(&mut reader, |record| {
+            self.convert(&Row {
+                headers: &headers,
+                record,
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::super::test_util::{errors, lines, NOW};
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Asia::Tokyo;
+
+    #[test]
+    fn read_test() {
+        let mapping: ColumnMapping =
+            toml::from_str(include_str!("../../../../testdata/import/custom.toml")).unwrap();
+        assert!(mapping.validate().is_empty());
+        let rows = parse(
+            &MappedCsv::new(mapping),
+            include_str!("../../../../testdata/import/custom.tsv"),
+            NOW,
+        )
+        .unwrap();
+        // 出勤・退勤が空の行は読み飛ばす
+        assert_eq!(lines(&rows), vec![2, 3, 5]);
+
+        // 日時の列をUTCで解釈する
+        let input = rows[0].input.as_ref().unwrap();
+        assert_eq!(input.uid, "alice");
+        assert_eq!(input.start_time, 1_601_542_800);
+        assert_eq!(input.end_time, 1_601_542_800 + 8 * 60 * 60);
+        assert_eq!(input.note, Some("remote".to_string()));
+        assert!(input.breaks.is_empty());
+        assert_eq!(errors(&rows[1]), vec!["end_time"]);
+        assert_eq!(errors(&rows[2]), vec!["start_time"]);
+    }
+
+    #[test]
+    fn clock_test() {
+        let date = NaiveDate::from_ymd(2020, 10, 1);
+        let mapping = ColumnMapping {
+            date: Some("date".to_string()),
+            ..toml::from_str("uid = \"uid\"\nstart_time = \"in\"\nend_time = \"out\"").unwrap()
+        };
+        let importer = MappedCsv::new(mapping);
+        let start = importer.datetime(Some(date), "22:00", None);
+        assert_eq!(start, Some(date.and_hms(22, 0, 0)));
+        // 出勤より前の時刻と24時以降の時刻は翌日
+        assert_eq!(
+            importer.datetime(Some(date), "6:00", start),
+            Some(date.succ().and_hms(6, 0, 0))
+        );
+        assert_eq!(
+            importer.datetime(Some(date), "30:00", start),
+            Some(date.succ().and_hms(6, 0, 0))
+        );
+        assert_eq!(importer.datetime(Some(date), "9時", None), None);
+        assert_eq!(
+            importer.timestamp(date.and_hms(9, 0, 0)),
+            Tokyo.ymd(2020, 10, 1).and_hms(9, 0, 0).timestamp()
+        );
+
+        assert_eq!(parse_duration("45"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("1:30"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration(""), Some(Duration::zero()));
+        assert_eq!(parse_duration("-10"), None);
+        assert_eq!(parse_duration("1000000000000"), None);
+        assert_eq!(parse_duration("9223372036854775807:00"), None);
+        assert_eq!(importer.datetime(Some(date), "48:00", start), None);
+        assert_eq!(importer.datetime(Some(date), "4294967295:00", start), None);
+        // 範囲外の日付
+        let last = chrono::naive::MAX_DATE;
+        assert_eq!(importer.datetime(Some(last), "6:00", None), None);
+        assert_eq!(
+            importer.datetime(Some(last), "6:00", Some(last.and_hms(22, 0, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_test() {
+        let mapping: ColumnMapping = toml::from_str(
+            "uid = \"uid\"\ndate = \"day\"\nstart_time = \"in\"\nend_time = \"out\"\nbreak_start = \"rest\"\ntime_zone = \"Mars/Olympus_Mons\"\ndelimiter = \"、\"",
+        )
+        .unwrap();
+        assert_eq!(mapping.validate().len(), 3);
+
+        // 日付の列が無く、time_formatにも日付が無い
+        let mapping: ColumnMapping =
+            toml::from_str("uid = \"uid\"\nstart_time = \"in\"\nend_time = \"out\"").unwrap();
+        assert_eq!(
+            mapping.validate(),
+            vec!["time_format must include the date when date is not set"]
+        );
+        for (time_format, valid) in &[
+            ("%Y-%m-%d %H:%M", true),
+            ("%m/%d %H:%M", false),
+            ("%Q", false),
+        ] {
+            let mapping = ColumnMapping {
+                time_format: time_format.to_string(),
+                ..mapping.clone()
+            };
+            assert_eq!(mapping.validate().is_empty(), *valid, "{}", time_format);
+        }
+
+        assert!(toml::from_str::<ColumnMapping>("uid = \"uid\"\nstart = \"in\"").is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! 過去の勤怠記録の一括登録.
+//!
+//! ファイルの形式毎の読み取りは`Importer`で行い、検証と登録はすべての形式で共通.
+mod json_lines;
+mod layouts;
+mod mapping;
+mod standard;
+
+pub use json_lines::JsonLines;
+pub use mapping::{ColumnMapping, MappedCsv};
+pub use standard::StandardCsv;
+
+use crate::database_utils::error::DataAccessError;
+use crate::usecase::attendance_records::add;
+use crate::usecase::validation::FieldError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 1回に登録できる行数の上限.
+pub const MAX_ROWS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// 1行でも登録できない場合はすべて取り消す
+    AllOrNothing,
+    /// 登録できない行を除いて登録する
+    SkipInvalid,
+}
+
+#[derive(Debug)]
+pub struct InputData {
+    pub mode: Mode,
+    pub body: String,
+}
+
+/// 読み取った行. 読み取りや検証に失敗した行はエラーを持つ.
+#[derive(Debug)]
+pub struct ImportRow {
+    /// ファイル中の行番号(1始まり)
+    pub line: u64,
+    pub input: Result<add::InputData, Vec<FieldError>>,
+}
+
+/// 行毎の結果.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowResult {
+    pub line: u64,
+    pub uid: Option<String>,
+    /// 登録したレコードのid. 取り消した場合もNone
+    pub record_id: Option<i32>,
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub failed: usize,
+    /// falseの場合、すべての登録を取り消した
+    pub committed: bool,
+    pub rows: Vec<RowResult>,
+}
+
+/// ファイルの形式毎の読み取り.
+pub trait Importer: Send + Sync {
+    /// 本文を行に分け、`add::InputData`に変換する. 値の検証は呼び出し側で行う.
+    ///
+    /// ファイル全体を読み取れない場合(必要な列が無いなど)は`Err`を返す.
+    fn read(&self, body: &str) -> Result<Vec<ImportRow>, DataAccessError>;
+}
+
+/// 組み込みの形式の名前. 設定ファイルのmappingには使えない.
+pub const BUILTIN_FORMATS: &[&str] = &[
+    "csv",
+    "json_lines",
+    layouts::DAILY_JP,
+    layouts::TIMESHEET_JP,
+    layouts::PUNCH_EN,
+];
+
+/// 形式の名前から`Importer`を選ぶ. 組み込みの形式に無い場合は`mappings`から探す.
+pub fn importer(
+    format: &str,
+    mappings: &HashMap<String, ColumnMapping>,
+) -> Result<Box<dyn Importer>, DataAccessError> {
+    match format {
+        "csv" => Ok(Box::new(StandardCsv)),
+        "json_lines" => Ok(Box::new(JsonLines)),
+        name => layouts::preset(name)
+            .or_else(|| mappings.get(name).cloned())
+            .map(|mapping| Box::new(MappedCsv::new(mapping)) as Box<dyn Importer>)
+            .ok_or_else(|| {
+                DataAccessError::validation("format", &format!("unknown format: {}", name))
+            }),
+    }
+}
+
+/// unix time、またはRFC 3339の日時.
+pub fn parse_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    value.parse::<i64>().ok().or_else(|| {
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|datetime| datetime.timestamp())
+    })
+}
+
+/// 本文を読み取り、行毎に`add::InputData`と同じ検証を行う.
+pub fn parse(
+    importer: &dyn Importer,
+    body: &str,
+    now: i64,
+) -> Result<Vec<ImportRow>, DataAccessError> {
+    let mut rows = importer.read(body.trim_start_matches('\u{feff}'))?;
+    if rows.is_empty() {
+        return Err(DataAccessError::validation("body", "must not be empty"));
+    }
+    if rows.len() > MAX_ROWS {
+        return Err(DataAccessError::validation(
+            "body",
+            &format!("must not have more than {} rows", MAX_ROWS),
+        ));
+    }
+
+    for row in rows.iter_mut() {
+        if let Ok(input) = &row.input {
+            if let Err(error) = input.validate(now) {
+                row.input = Err(row_errors(error));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// 行の登録に失敗した理由を項目毎のエラーにする.
+pub fn row_errors(error: DataAccessError) -> Vec<FieldError> {
+    match error {
+        DataAccessError::Validation(errors) => errors,
+        // ユーザーが存在しない、または無効化されている
+        DataAccessError::NotFound(message) | DataAccessError::Forbidden(message) => {
+            vec![FieldError::new("uid", &message)]
+        }
+        // 既存のレコード、または先に登録した行と勤務時間が重なる
+        DataAccessError::Conflict(message) => vec![FieldError::new("start_time", &message)],
+        error => vec![FieldError::new("line", &error.to_string())],
+    }
+}
+
+/// CSVの列名の行を読み、必要な列が揃っているか確かめる.
+fn read_headers<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+    required: &[&str],
+) -> Result<csv::StringRecord, DataAccessError> {
+    let headers = reader
+        .headers()
+        .map_err(|e| DataAccessError::validation("body", &e.to_string()))?
+        .clone();
+    for column in required {
+        if !headers.iter().any(|header| header == *column) {
+            return Err(DataAccessError::validation(
+                "body",
+                &format!("missing column: {}", column),
+            ));
+        }
+    }
+    Ok(headers)
+}
+
+/// CSVの各行を`convert`で変換する. 列の数が合わない行はエラーとする.
+fn read_records<R, F>(reader: &mut csv::Reader<R>, mut convert: F) -> Vec<ImportRow>
+where
+    R: std::io::Read,
+    F: FnMut(&csv::StringRecord) -> Option<Result<add::InputData, Vec<FieldError>>>,
+{
+    reader
+        .records()
+        .filter_map(|record| match record {
+            Ok(record) => convert(&record).map(|input| ImportRow {
+                line: record.position().map(|p| p.line()).unwrap_or(0),
+                input,
+            }),
+            Err(e) => Some(ImportRow {
+                line: e.position().map(|p| p.line()).unwrap_or(0),
+                input: Err(vec![FieldError::new("line", &e.to_string())]),
+            }),
+        })
+        .collect()
+}
+
+pub trait ImportRecordsUseCase {
+    /// 1つのtransactionで登録する. `Mode::AllOrNothing`で失敗した行がある場合は取り消す.
+    fn import_records(
+        &self,
+        rows: Vec<ImportRow>,
+        mode: Mode,
+    ) -> Result<ImportReport, DataAccessError>;
+}
+
+pub fn execute<T>(
+    data_access: T,
+    importer: &dyn Importer,
+    input: InputData,
+) -> Result<ImportReport, DataAccessError>
+where
+    T: ImportRecordsUseCase,
+{
+    let rows = parse(importer, &input.body, Utc::now().timestamp())?;
+
+    data_access.import_records(rows, input.mode)
+}
+
+#[cfg(test)]
+pub mod test_util {
+    use super::*;
+
+    /// 検証の基準とする現在時刻. fixtureの日時はこれより前.
+    pub const NOW: i64 = 1_605_000_000;
+
+    /// 行毎のエラーの項目名.
+    pub fn errors(row: &ImportRow) -> Vec<&str> {
+        match &row.input {
+            Ok(_) => vec![],
+            Err(errors) => errors.iter().map(|error| error.field.as_str()).collect(),
+        }
+    }
+
+    pub fn lines(rows: &[ImportRow]) -> Vec<u64> {
+        rows.iter().map(|row| row.line).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn importer_test() {
+        let mut mappings = HashMap::new();
+        mappings.insert(
+            "custom".to_string(),
+            toml::from_str::<ColumnMapping>(
+                "uid = \"id\"\nstart_time = \"in\"\nend_time = \"out\"\ndate = \"day\"",
+            )
+            .unwrap(),
+        );
+
+        for format in BUILTIN_FORMATS.iter().chain(&["custom"]) {
+            assert!(importer(format, &mappings).is_ok(), "{}", format);
+        }
+        assert!(importer("xml", &mappings).is_err());
+
+        let rows = parse(
+            importer("custom", &mappings).unwrap().as_ref(),
+            "\u{feff}id,day,in,out\nalice,2020/10/01,9:00,18:00\n",
+            test_util::NOW,
+        )
+        .unwrap();
+        assert!(rows[0].input.is_ok());
+    }
+}
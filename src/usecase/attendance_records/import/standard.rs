@@ -0,0 +1,117 @@
+use super::{parse_time, read_headers, read_records, ImportRow, Importer};
+use crate::database_utils::error::DataAccessError;
+use crate::usecase::attendance_records::add::{self, BreakInput};
+use crate::usecase::validation::FieldError;
+use serde::Deserialize;
+
+/// 1行目が列名のCSV. 列は`uid,start_time,end_time,breaks,note`で、`breaks`と`note`は省略できる.
+///
+/// 時刻はunix timeかRFC 3339. 休憩は`開始/終了`を`;`で区切る.
+#[derive(Debug, Default)]
+pub struct StandardCsv;
+
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    uid: String,
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    breaks: String,
+    #[serde(default)]
+    note: String,
+}
+
+impl CsvRow {
+    fn to_input_data(&self) -> Result<add::InputData, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let mut time = |field: &str, value: &str| {
+            parse_time(value).unwrap_or_else(|| {
+                errors.push(FieldError::new(
+                    field,
+                    "must be a unix time or an RFC 3339 date time",
+                ));
+                0
+            })
+        };
+        let start_time = time("start_time", &self.start_time);
+        let end_time = time("end_time", &self.end_time);
+        let breaks = self
+            .breaks
+            .split(';')
+            .filter(|item| !item.trim().is_empty())
+            .enumerate()
+            .map(|(index, item)| {
+                let mut times = item.splitn(2, '/');
+                let field = |name: &str| format!("breaks[{}].{}", index, name);
+                BreakInput {
+                    start_time: time(&field("start_time"), times.next().unwrap_or("")),
+                    end_time: time(&field("end_time"), times.next().unwrap_or("")),
+                }
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(add::InputData {
+            uid: self.uid.trim().to_string(),
+            start_time,
+            end_time,
+            breaks,
+            note: Some(self.note.clone()).filter(|note| !note.is_empty()),
+        })
+    }
+}
+
+impl Importer for StandardCsv {
+    fn read(&self, body: &str) -> Result<Vec<ImportRow>, DataAccessError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::Headers)
+            .from_reader(body.as_bytes());
+        let headers = read_headers(&mut reader, &["uid", "start_time", "end_time"])?;
+
+        Ok(read_records(&mut reader, |record| {
+            Some(
+                record
+                    .deserialize::<CsvRow>(Some(&headers))
+                    .map_err(|e| vec![FieldError::new("line", &e.to_string())])
+                    .and_then(|row| row.to_input_data()),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::super::test_util::{errors, lines, NOW};
+    use super::*;
+
+    #[test]
+    fn read_test() {
+        let rows = parse(
+            &StandardCsv,
+            include_str!("../../../../testdata/import/standard.csv"),
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(lines(&rows), vec![2, 3, 4, 5]);
+
+        let input = rows[0].input.as_ref().unwrap();
+        assert_eq!(input.uid, "alice");
+        assert_eq!(input.start_time, 1_601_510_400);
+        assert_eq!(input.end_time, 1_601_510_400 + 9 * 60 * 60);
+        assert_eq!(input.breaks.len(), 1);
+        assert_eq!(input.breaks[0].start_time, 1_601_510_400 + 3 * 60 * 60);
+        assert_eq!(input.note, Some("早退, 再出勤".to_string()));
+
+        // 開始と終了が逆
+        assert_eq!(errors(&rows[1]), vec!["end_time"]);
+        assert_eq!(errors(&rows[2]), vec!["start_time"]);
+        // 列が足りない
+        assert_eq!(errors(&rows[3]), vec!["line"]);
+
+        assert!(parse(&StandardCsv, "uid,start_time\nalice,0\n", NOW).is_err());
+        assert!(parse(&StandardCsv, "uid,start_time,end_time\n", NOW).is_err());
+    }
+}
@@ -1,4 +1,11 @@
 pub mod add;
+pub mod break_end;
+pub mod break_start;
+pub mod clock_in;
+pub mod clock_out;
 pub mod delete;
+pub mod export;
+pub mod import;
 pub mod search_by_user;
+pub mod summary;
 pub mod update;
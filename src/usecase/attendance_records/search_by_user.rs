@@ -1,24 +1,88 @@
 use crate::database_utils::error::DataAccessError;
-use crate::domain::entity::attendance_record::AttendanceRecord;
+use crate::domain::entity::record_page::RecordPage;
+use crate::usecase::validation::Validator;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 1ページに取得できるレコード数の上限.
+pub const MAX_COUNT: i32 = 100;
+
+/// 並び替えの基準.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    StartTime,
+    EndTime,
+    Id,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct InputData {
     pub uid: String,
     pub page: i32,
     pub count: i32,
+    /// 前のページの`next_cursor`. 指定された場合は`page`を使用しない
+    pub cursor: Option<String>,
+    /// 出勤時刻がfrom以降のレコードに絞り込む
+    pub from: Option<i64>,
+    /// 出勤時刻がtoより前のレコードに絞り込む
+    pub to: Option<i64>,
+    /// 省略時はid. `cursor`を指定した場合は出勤時刻
+    pub sort: Option<SortKey>,
+    /// 省略時は降順
+    pub order: Option<SortOrder>,
+    /// 退勤前のレコードのみ
+    pub open_only: bool,
+    /// 備考が入力されたレコードのみ
+    pub has_note: bool,
+    /// 条件に一致するレコードの総数を取得するか
+    pub with_total: bool,
+}
+
+impl InputData {
+    /// 並び替えの基準. 省略時は従来のページ番号での取得と同じidの順.
+    pub fn sort_key(&self) -> SortKey {
+        match (self.sort, &self.cursor) {
+            (Some(sort), _) => sort,
+            (None, Some(_)) => SortKey::StartTime,
+            (None, None) => SortKey::Id,
+        }
+    }
 }
 
 pub trait SearchRecordsByUserUseCase {
-    fn get_records(&self, input: InputData) -> Result<Vec<AttendanceRecord>, DataAccessError>;
+    fn get_records(&self, input: InputData) -> Result<RecordPage, DataAccessError>;
 }
 
-pub fn execute<T>(
-    data_access: T,
-    input: InputData,
-) -> Result<Vec<AttendanceRecord>, DataAccessError>
+pub fn execute<T>(data_access: T, input: InputData) -> Result<RecordPage, DataAccessError>
 where
     T: SearchRecordsByUserUseCase,
 {
+    let mut validator = Validator::new();
+    if input.page < 1 {
+        validator.add("page", "must be 1 or greater");
+    }
+    if input.count < 1 || input.count > MAX_COUNT {
+        validator.add("count", &format!("must be between 1 and {}", MAX_COUNT));
+    }
+    if input.cursor.is_some() && input.sort_key() != SortKey::StartTime {
+        validator.add("cursor", "can only be used with sort=start_time");
+    }
+    let now = Utc::now().timestamp();
+    if let Some(from) = input.from {
+        validator.timestamp("from", from, now);
+    }
+    if let Some(to) = input.to {
+        validator.timestamp("to", to, now);
+    }
+    validator.finish()?;
+
     data_access.get_records(input)
 }
@@ -0,0 +1,88 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::work_summary::WorkSummary;
+use crate::usecase::validation::Validator;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Day,
+    Week,
+    Month,
+}
+
+impl GroupBy {
+    /// postgresの`date_trunc`に渡す単位.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupBy::Day => "day",
+            GroupBy::Week => "week",
+            GroupBy::Month => "month",
+        }
+    }
+
+    /// 日時が属する集計単位の開始日時. postgresの`date_trunc`と同じく週は月曜日始まり.
+    pub fn truncate(&self, datetime: NaiveDateTime) -> NaiveDateTime {
+        let date = datetime.date();
+        let start = match self {
+            GroupBy::Day => date,
+            GroupBy::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            GroupBy::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+        };
+        start.and_hms(0, 0, 0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+    /// 集計期間の開始(含む). 出勤時刻で判定する.
+    pub from: i64,
+    /// 集計期間の終了(含まない). 出勤時刻で判定する.
+    pub to: i64,
+    pub group_by: GroupBy,
+}
+
+impl InputData {
+    pub fn validate(&self, now: i64) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        validator.period(self.from, self.to, now);
+        validator.finish()
+    }
+}
+
+/// 退勤済みのレコードを集計する.
+pub trait SummarizeRecordsUseCase {
+    fn summarize(&self, input: InputData) -> Result<WorkSummary, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<WorkSummary, DataAccessError>
+where
+    T: SummarizeRecordsUseCase,
+{
+    input.validate(Utc::now().timestamp())?;
+
+    data_access.summarize(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: i64 = 1_605_000_000;
+
+    #[test]
+    fn validate_test() {
+        let input = |from: i64, to: i64| InputData {
+            uid: "alice".to_string(),
+            from,
+            to,
+            group_by: GroupBy::Day,
+        };
+        assert!(input(0, NOW).validate(NOW).is_ok());
+        assert!(input(NOW, 0).validate(NOW).is_err());
+        assert!(input(0, 99_999_999_999_999_999).validate(NOW).is_err());
+        assert!(input(-99_999_999_999_999_999, NOW).validate(NOW).is_err());
+    }
+}
@@ -1,4 +1,7 @@
 use crate::database_utils::error::DataAccessError;
+use crate::usecase::attendance_records::add::BreakInput;
+use crate::usecase::validation::Validator;
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -7,7 +10,35 @@ pub struct InputData {
     pub id: i32,
     pub start_time: Option<i64>,
     pub end_time: Option<i64>,
-    pub break_time: Option<i32>,
+    /// 指定された場合、既存の休憩をすべて置き換える.
+    pub breaks: Option<Vec<BreakInput>>,
+    pub note: Option<String>,
+}
+
+impl InputData {
+    /// 指定された項目のみを検証する. 既存の値との整合性はドライバーで検証する.
+    pub fn validate(&self, now: i64) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        if let Some(start_time) = self.start_time {
+            validator.timestamp("start_time", start_time, now);
+        }
+        if let Some(end_time) = self.end_time {
+            validator.timestamp("end_time", end_time, now);
+        }
+        let shift = match (self.start_time, self.end_time) {
+            (Some(start_time), Some(end_time)) => {
+                validator.shift(start_time, end_time);
+                Some((start_time, end_time))
+            }
+            _ => None,
+        };
+        if let Some(breaks) = &self.breaks {
+            validator.breaks(breaks, shift, now);
+        }
+        validator.note(&self.note);
+
+        validator.finish()
+    }
 }
 
 pub trait UpdateRecordUseCase {
@@ -18,5 +49,7 @@ pub fn execute<T>(data_access: T, input: InputData) -> Result<(), DataAccessErro
 where
     T: UpdateRecordUseCase,
 {
+    input.validate(Utc::now().timestamp())?;
+
     data_access.update_record(input)
 }
@@ -0,0 +1,14 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::integrity_issue::IntegrityIssue;
+
+pub trait CheckIntegrityUseCase {
+    /// すべてのユーザーの勤怠記録を検査し、検出した不整合をuid順に返す.
+    fn check_integrity(&self) -> Result<Vec<IntegrityIssue>, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T) -> Result<Vec<IntegrityIssue>, DataAccessError>
+where
+    T: CheckIntegrityUseCase,
+{
+    data_access.check_integrity()
+}
@@ -1,2 +1,6 @@
+pub mod api_keys;
 pub mod attendance_records;
+pub mod integrity;
+pub mod payroll;
 pub mod users;
+pub mod validation;
@@ -0,0 +1,72 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::payroll::{Payroll, PremiumRates};
+use crate::usecase::validation::Validator;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+    /// 計算期間の開始(含む). 出勤時刻で判定する.
+    pub from: i64,
+    /// 計算期間の終了(含まない). 出勤時刻で判定する.
+    pub to: i64,
+    /// 設定(`payroll`)の割増率.
+    pub rates: PremiumRates,
+}
+
+impl InputData {
+    pub fn validate(&self, now: i64) -> Result<(), DataAccessError> {
+        let mut validator = Validator::new();
+        validator.period(self.from, self.to, now);
+        if let Err(message) = self.rates.validate() {
+            validator.add("rates", &message);
+        }
+        validator.finish()
+    }
+}
+
+/// 期間内の退勤済みレコードから支給額を計算する.
+/// 勤務日に適用される時給が登録されていない場合はエラーとする.
+pub trait CalculatePayrollUseCase {
+    fn calculate_payroll(&self, input: InputData) -> Result<Payroll, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<Payroll, DataAccessError>
+where
+    T: CalculatePayrollUseCase,
+{
+    input.validate(Utc::now().timestamp())?;
+
+    data_access.calculate_payroll(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: i64 = 1_605_000_000;
+
+    #[test]
+    fn validate_test() {
+        let input = |from: i64, to: i64| InputData {
+            uid: "alice".to_string(),
+            from,
+            to,
+            rates: PremiumRates::default(),
+        };
+        assert!(input(0, NOW).validate(NOW).is_ok());
+        assert!(input(NOW, 0).validate(NOW).is_err());
+        assert!(input(0, 99_999_999_999_999_999).validate(NOW).is_err());
+        assert!(input(-99_999_999_999_999_999, NOW).validate(NOW).is_err());
+
+        let input = InputData {
+            rates: PremiumRates {
+                overtime: 1_000_000,
+                ..PremiumRates::default()
+            },
+            ..input(0, NOW)
+        };
+        assert!(input.validate(NOW).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::hourly_wage::HourlyWage;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+    pub amount: i32,
+    pub effective_from: NaiveDate,
+}
+
+/// 時給を登録する. 同じ適用開始日の時給が登録済みの場合は上書きする.
+pub trait SetWageUseCase {
+    fn set_wage(&self, input: InputData) -> Result<HourlyWage, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<HourlyWage, DataAccessError>
+where
+    T: SetWageUseCase,
+{
+    data_access.set_wage(input)
+}
@@ -1,19 +1,27 @@
 use crate::database_utils::error::DataAccessError;
-use crate::domain::entity::user::User;
+use crate::domain::entity::user::{validate_time_zone, User};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InputData {
     pub uid: String,
+    /// IANA time zone名. 省略時はAsia/Tokyo
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
 pub trait CreateUserUseCase {
-    fn create(&self, uid: String) -> Result<User, DataAccessError>;
+    fn create(&self, uid: String, time_zone: Option<String>) -> Result<User, DataAccessError>;
 }
 
 pub fn execute<T>(data_access: T, input: InputData) -> Result<User, DataAccessError>
 where
     T: CreateUserUseCase,
 {
-    data_access.create(input.uid)
+    if let Some(time_zone) = &input.time_zone {
+        validate_time_zone(time_zone)
+            .map_err(|message| DataAccessError::validation("time_zone", &message))?;
+    }
+
+    data_access.create(input.uid, input.time_zone)
 }
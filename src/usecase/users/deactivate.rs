@@ -0,0 +1,20 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::user::User;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+}
+
+pub trait DeactivateUserUseCase {
+    /// 無効化する. 無効化済みの場合はそのまま返す.
+    fn deactivate(&self, uid: String) -> Result<User, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<User, DataAccessError>
+where
+    T: DeactivateUserUseCase,
+{
+    data_access.deactivate(input.uid)
+}
@@ -0,0 +1,14 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::user::User;
+
+pub trait ListUsersUseCase {
+    /// 無効化したユーザーも含め、id順に返す.
+    fn list_users(&self) -> Result<Vec<User>, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T) -> Result<Vec<User>, DataAccessError>
+where
+    T: ListUsersUseCase,
+{
+    data_access.list_users()
+}
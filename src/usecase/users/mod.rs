@@ -1,3 +1,6 @@
 pub mod add;
 pub mod check;
+pub mod deactivate;
 pub mod delete;
+pub mod list;
+pub mod update;
@@ -0,0 +1,23 @@
+use crate::database_utils::error::DataAccessError;
+use crate::domain::entity::user::{validate_time_zone, User};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputData {
+    pub uid: String,
+    pub time_zone: String,
+}
+
+pub trait UpdateUserUseCase {
+    fn update_time_zone(&self, uid: String, time_zone: String) -> Result<User, DataAccessError>;
+}
+
+pub fn execute<T>(data_access: T, input: InputData) -> Result<User, DataAccessError>
+where
+    T: UpdateUserUseCase,
+{
+    validate_time_zone(&input.time_zone)
+        .map_err(|message| DataAccessError::validation("time_zone", &message))?;
+
+    data_access.update_time_zone(input.uid, input.time_zone)
+}
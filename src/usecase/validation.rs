@@ -0,0 +1,209 @@
+//! 入力値の検証. エラーは項目毎にまとめて返す.
+use crate::database_utils::error::DataAccessError;
+use crate::usecase::attendance_records::add::BreakInput;
+use serde::{Deserialize, Serialize};
+
+/// 現在時刻より先の時刻として受け付ける上限(秒).
+pub const MAX_FUTURE_SECONDS: i64 = 24 * 60 * 60;
+/// 1回の勤務として受け付ける長さの上限(秒).
+pub const MAX_SHIFT_SECONDS: i64 = 24 * 60 * 60;
+/// 備考の最大文字数.
+pub const MAX_NOTE_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// 入力項目の名前. 配列の要素は`breaks[0].start_time`の形式
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 検証エラーを蓄積する.
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Validator {
+        Validator::default()
+    }
+
+    pub fn add(&mut self, field: &str, message: &str) {
+        self.errors.push(FieldError::new(field, message));
+    }
+
+    /// 0(1970-01-01)より前、または現在時刻から`MAX_FUTURE_SECONDS`より先の時刻を拒否する.
+    pub fn timestamp(&mut self, field: &str, value: i64, now: i64) {
+        if value < 0 {
+            self.add(field, "must not be negative");
+        } else if value > now + MAX_FUTURE_SECONDS {
+            self.add(field, "must not be more than 24 hours in the future");
+        }
+    }
+
+    /// 検索や集計の期間. `from`(含む)から`to`(含まない)までで、どちらも`timestamp`と同じ範囲.
+    pub fn period(&mut self, from: i64, to: i64, now: i64) {
+        self.timestamp("from", from, now);
+        self.timestamp("to", to, now);
+        if to <= from {
+            self.add("to", "must be after from");
+        }
+    }
+
+    /// 勤務の開始・終了時刻の前後関係と長さ.
+    pub fn shift(&mut self, start_time: i64, end_time: i64) {
+        if end_time <= start_time {
+            self.add("end_time", "must be after start_time");
+        } else if end_time - start_time > MAX_SHIFT_SECONDS {
+            self.add("end_time", "must be within 24 hours of start_time");
+        }
+    }
+
+    /// 休憩それぞれの時刻と、勤務時間が分かる場合はその範囲内に収まるか.
+    pub fn breaks(&mut self, breaks: &[BreakInput], shift: Option<(i64, i64)>, now: i64) {
+        let mut total = 0;
+        for (index, item) in breaks.iter().enumerate() {
+            let field = |name: &str| format!("breaks[{}].{}", index, name);
+            self.timestamp(&field("start_time"), item.start_time, now);
+            self.timestamp(&field("end_time"), item.end_time, now);
+            if item.end_time < item.start_time {
+                self.add(&field("end_time"), "must not be before start_time");
+                continue;
+            }
+            total += item.end_time - item.start_time;
+
+            if let Some((start_time, end_time)) = shift {
+                if item.start_time < start_time {
+                    self.add(&field("start_time"), "must not be before the shift starts");
+                }
+                if item.end_time > end_time {
+                    self.add(&field("end_time"), "must not be after the shift ends");
+                }
+            }
+        }
+
+        if let Some((start_time, end_time)) = shift {
+            if end_time > start_time && total >= end_time - start_time {
+                self.add("breaks", "must be shorter than the shift in total");
+            }
+        }
+    }
+
+    pub fn note(&mut self, note: &Option<String>) {
+        if let Some(note) = note {
+            if note.chars().count() > MAX_NOTE_LENGTH {
+                self.add("note", "must be at most 1000 characters");
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), DataAccessError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DataAccessError::Validation(self.errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: i64 = 1_601_510_400;
+    const HOUR: i64 = 60 * 60;
+
+    fn errors(validator: Validator) -> Vec<String> {
+        match validator.finish() {
+            Ok(()) => vec![],
+            Err(DataAccessError::Validation(errors)) => {
+                errors.into_iter().map(|error| error.field).collect()
+            }
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn timestamp_test() {
+        let mut validator = Validator::new();
+        validator.timestamp("a", NOW, NOW);
+        validator.timestamp("b", NOW + MAX_FUTURE_SECONDS, NOW);
+        assert!(errors(validator).is_empty());
+
+        let mut validator = Validator::new();
+        validator.timestamp("a", -1, NOW);
+        validator.timestamp("b", NOW + MAX_FUTURE_SECONDS + 1, NOW);
+        assert_eq!(errors(validator), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn period_test() {
+        let mut validator = Validator::new();
+        validator.period(0, NOW, NOW);
+        assert!(errors(validator).is_empty());
+
+        let mut validator = Validator::new();
+        validator.period(NOW, NOW, NOW);
+        assert_eq!(errors(validator), vec!["to"]);
+
+        // 日時に変換できない値
+        let mut validator = Validator::new();
+        validator.period(i64::MIN, i64::MAX, NOW);
+        assert_eq!(errors(validator), vec!["from", "to"]);
+    }
+
+    #[test]
+    fn shift_test() {
+        let mut validator = Validator::new();
+        validator.shift(NOW, NOW + 8 * HOUR);
+        assert!(errors(validator).is_empty());
+
+        for end_time in &[NOW, NOW - HOUR, NOW + 25 * HOUR] {
+            let mut validator = Validator::new();
+            validator.shift(NOW, *end_time);
+            assert_eq!(errors(validator), vec!["end_time"]);
+        }
+    }
+
+    #[test]
+    fn breaks_test() {
+        let item = |start: i64, end: i64| BreakInput {
+            start_time: NOW + start * HOUR,
+            end_time: NOW + end * HOUR,
+        };
+        let shift = Some((NOW, NOW + 8 * HOUR));
+
+        let mut validator = Validator::new();
+        validator.breaks(&[item(3, 4)], shift, NOW);
+        assert!(errors(validator).is_empty());
+
+        let mut validator = Validator::new();
+        validator.breaks(&[item(4, 3), item(-1, 1), item(7, 9)], shift, NOW);
+        assert_eq!(
+            errors(validator),
+            vec![
+                "breaks[0].end_time",
+                "breaks[1].start_time",
+                "breaks[2].end_time"
+            ]
+        );
+
+        let mut validator = Validator::new();
+        validator.breaks(&[item(0, 8)], shift, NOW);
+        assert_eq!(errors(validator), vec!["breaks"]);
+
+        // 勤務時間が不明な場合は範囲を検証しない
+        let mut validator = Validator::new();
+        validator.breaks(&[item(-1, 1)], None, NOW);
+        assert!(errors(validator).is_empty());
+    }
+}